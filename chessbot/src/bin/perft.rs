@@ -0,0 +1,25 @@
+// perft <suite.epd> [--depth N] [--hash], checks a whole perft suite without starting the engine.
+// the crate has no library so the move generation is compiled into this binary as well
+#![allow(dead_code)]
+
+use std::process::ExitCode;
+
+#[path = "../board/mod.rs"]
+mod board;
+#[path = "../constants.rs"]
+mod constants;
+#[path = "../movegeneration/mod.rs"]
+mod movegeneration;
+#[path = "../utils.rs"]
+mod utils;
+
+fn main() -> ExitCode{
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match movegeneration::perft::run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use std::fmt;
+
+use crate::board::{color::Color, pice::{Pice, PiceType}, state::{CastleRights, State}, Board};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError{
+    MissingField(&'static str),
+    InvalidPlacement(String),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidCounter(String),
+    KingCount(Color, usize),
+    TooManyPices(Color),
+    PawnOnBackRank(u8),
+    OpponentInCheck,
+    CastlingWithoutRook(char),
+    ImplausibleEnPassant(String)
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {}", field),
+            FenError::InvalidPlacement(placement) => write!(f, "invalid pice placement {}", placement),
+            FenError::InvalidSideToMove(side) => write!(f, "invalid side to move {}", side),
+            FenError::InvalidCastling(castling) => write!(f, "invalid castling rights {}", castling),
+            FenError::InvalidEnPassant(square) => write!(f, "invalid en passant square {}", square),
+            FenError::InvalidCounter(counter) => write!(f, "invalid move counter {}", counter),
+            FenError::KingCount(color, count) => write!(f, "{:?} has {} kings", color, count),
+            FenError::TooManyPices(color) => write!(f, "{:?} has too many pices", color),
+            FenError::PawnOnBackRank(square) => write!(f, "pawn on {}", square_name(*square)),
+            FenError::OpponentInCheck => write!(f, "the side not to move is in check"),
+            FenError::CastlingWithoutRook(right) => write!(f, "castling right {} without king and rook in place", right),
+            FenError::ImplausibleEnPassant(square) => write!(f, "no pawn could have moved past {}", square),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+fn square_name(square: u8) -> String{
+    format!("{}{}", (b'a' + square % 8) as char, square / 8 + 1)
+}
+
+// castling right, the king and rook squares it needs
+const CASTLING: [(char, CastleRights, u8, u8); 4] = [
+    ('K', CastleRights::WhiteKingside, 4, 7),
+    ('Q', CastleRights::WhiteQueenside, 4, 0),
+    ('k', CastleRights::BlackKingside, 60, 63),
+    ('q', CastleRights::BlackQueenside, 60, 56),
+];
+
+impl Board {
+    // parse and validate a fen, the move counters are optional since epd positions dont have them
+    pub fn try_from_fen(fen: &str) -> Result<Board, FenError>{
+        let board = Board::parse_fen(fen)?;
+        board.validate()?;
+        Ok(board)
+    }
+
+    // only checks the syntax, the position itself may be illegal
+    pub(super) fn parse_fen(fen: &str) -> Result<Board, FenError>{
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let placement = *fields.first().ok_or(FenError::MissingField("pice placement"))?;
+        let side = *fields.get(1).ok_or(FenError::MissingField("side to move"))?;
+        let castling = *fields.get(2).ok_or(FenError::MissingField("castling rights"))?;
+        let passant = *fields.get(3).ok_or(FenError::MissingField("en passant square"))?;
+
+        let mut pices: Vec<Pice> = vec![];
+        let mut board: [Option<usize>;64] = [None;64];
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::InvalidPlacement(placement.into()));
+        }
+        for (rank, row) in ranks.iter().enumerate(){
+            let mut file = 0;
+            for c in row.chars(){
+                match c {
+                    // checked before adding so a long run of digits can't overflow
+                    '1'..='8' if file + (c as u8 - b'0') <= 8 => file += c as u8 - b'0',
+                    'K' | 'Q' | 'R' | 'B' | 'N' | 'P' | 'k' | 'q' | 'r' | 'b' | 'n' | 'p' if file < 8 => {
+                        let pos = (7 - rank as u8) * 8 + file;
+                        board[pos as usize] = Some(pices.len());
+                        pices.push(Pice::from_char(c, pos));
+                        file += 1;
+                    },
+                    _ => return Err(FenError::InvalidPlacement(placement.into()))
+                }
+            }
+            if file != 8 {
+                return Err(FenError::InvalidPlacement(placement.into()));
+            }
+        }
+
+        // the move generation needs exactly one king per side and no pawns on the first or last rank
+        for color in [Color::White, Color::Black]{
+            let kings = pices.iter().filter(|pice| pice.pice_type() == PiceType::King && pice.color() == color).count();
+            if kings != 1 {
+                return Err(FenError::KingCount(color, kings));
+            }
+        }
+        if let Some(pawn) = pices.iter().find(|pice| pice.pice_type() == PiceType::Pawn && (pice.pos < 8 || pice.pos >= 56)) {
+            return Err(FenError::PawnOnBackRank(pawn.pos));
+        }
+
+        let turn = match side {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(FenError::InvalidSideToMove(side.into()))
+        };
+
+        let valid_castling = castling == "-" || (castling.chars().all(|c| "KQkq".contains(c))
+            && castling.chars().enumerate().all(|(idx, c)| !castling[idx + 1..].contains(c)));
+        if !valid_castling {
+            return Err(FenError::InvalidCastling(castling.into()));
+        }
+        let mut state = State::from_pices(&pices, 0, 0);
+        state.castle_rights = CastleRights::str_to_casle_rights(castling);
+
+        if passant != "-" {
+            let square = Board::parse_square(passant).filter(|square| square / 8 == 2 || square / 8 == 5);
+            state.passant = 1 << square.ok_or(FenError::InvalidEnPassant(passant.into()))?;
+        }
+
+        let counter = |field: Option<&&str>, default: u16| match field {
+            Some(value) => value.parse::<u16>().map_err(|_| FenError::InvalidCounter(value.to_string())),
+            None => Ok(default)
+        };
+        let halfmove_clock = counter(fields.get(4), 0)?;
+        let fullmove_number = counter(fields.get(5), 1)?.max(1);
+
+        let mut res = Board::new(pices, board, turn, state);
+        res.halfmove_clock = halfmove_clock;
+        res.fullmove_number = fullmove_number;
+        res.update_moves(res.turn.other());
+        res.update_moves(res.turn);
+        Ok(res)
+    }
+
+    // checks that the position could occur in a game
+    fn validate(&self) -> Result<(), FenError>{
+        let pices: Vec<&Pice> = self.pices.iter().filter(|pice| !pice.is_captured()).collect();
+        for color in [Color::White, Color::Black]{
+            let own: Vec<&&Pice> = pices.iter().filter(|pice| pice.color() == color).collect();
+            let count = |typ: PiceType| own.iter().filter(|pice| pice.pice_type() == typ).count();
+            // every pice beyond the starting set has to be a promoted pawn
+            let promoted = count(PiceType::Queen).saturating_sub(1) + count(PiceType::Rook).saturating_sub(2)
+                + count(PiceType::Bishop).saturating_sub(2) + count(PiceType::Knight).saturating_sub(2);
+            if own.len() > 16 || count(PiceType::Pawn) + promoted > 8 {
+                return Err(FenError::TooManyPices(color));
+            }
+        }
+
+        let pice_at = |pos: u8, typ: PiceType, color: Color| self.get_pice_pos(pos).is_some_and(|pice| pice.pice_type() == typ && pice.color() == color);
+        for (right, castle_right, king, rook) in CASTLING{
+            let color = if right.is_uppercase() { Color::White } else { Color::Black };
+            if self.state.casle_right(castle_right) && !(pice_at(king, PiceType::King, color) && pice_at(rook, PiceType::Rook, color)) {
+                return Err(FenError::CastlingWithoutRook(right));
+            }
+        }
+
+        if self.state.passant != 0 {
+            let square = self.state.passant.trailing_zeros() as u8;
+            // the pawn that moved two squares is in front of the en passant square, seen from the side to move
+            let (rank, pawn, start) = match self.turn {
+                Color::White => (5, square - 8, square + 8),
+                Color::Black => (2, square + 8, square - 8),
+            };
+            let plausible = square / 8 == rank && pice_at(pawn, PiceType::Pawn, self.turn.other())
+                && self.get_pice_pos(square).is_none() && self.get_pice_pos(start).is_none();
+            if !plausible {
+                return Err(FenError::ImplausibleEnPassant(square_name(square)));
+            }
+        }
+
+        if self.state.in_check(self.turn.other()) {
+            return Err(FenError::OpponentInCheck);
+        }
+        Ok(())
+    }
+
+    pub fn to_fen(&self) -> String{
+        let mut placement = String::new();
+        for rank in (0..8).rev(){
+            let mut empty = 0;
+            for file in 0..8{
+                match self.get_pice_pos(rank * 8 + file) {
+                    Some(pice) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push_str(&pice.char());
+                    },
+                    None => empty += 1
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+        let side = if self.turn == Color::White { "w" } else { "b" };
+        let castling: String = CASTLING.iter()
+            .filter(|(_, castle_right, _, _)| self.state.casle_right(*castle_right))
+            .map(|(right, _, _, _)| *right)
+            .collect();
+        let castling = if castling.is_empty() { "-".into() } else { castling };
+        let passant = match self.state.passant {
+            0 => "-".into(),
+            passant => square_name(passant.trailing_zeros() as u8)
+        };
+        format!("{} {} {} {} {} {}", placement, side, castling, passant, self.halfmove_clock, self.fullmove_number)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::board::{color::Color, Board};
+
+    use super::FenError;
+
+    #[test]
+    fn fen_round_trip(){
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w Kq f6 0 3",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 12 40",
+        ]{
+            assert_eq!(Board::try_from_fen(fen).unwrap().to_fen(), fen);
+        }
+        let mut board = Board::default();
+        let mv = board.move_from_long_algebraic_notation("e2e4".into()).unwrap();
+        board.make_move(mv);
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        assert_eq!(Board::try_from_fen("8/8/8/8/8/8/8/K6k w - -").unwrap().to_fen(), "8/8/8/8/8/8/8/K6k w - - 0 1");
+    }
+
+    #[test]
+    fn invalid_fens(){
+        let err = |fen: &str| Board::try_from_fen(fen).err();
+        assert_eq!(err(""), Some(FenError::MissingField("pice placement")));
+        assert_eq!(err("8/8/8/8/8/8/8/K6k w"), Some(FenError::MissingField("castling rights")));
+        assert!(matches!(err("8/8/8/8/8/8/K6k w - -"), Some(FenError::InvalidPlacement(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6x w - -"), Some(FenError::InvalidPlacement(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K7k w - -"), Some(FenError::InvalidPlacement(_))));
+        assert!(matches!(err(&format!("{}/8/8/8/8/8/8/K6k w - - 0 1", "8".repeat(40))), Some(FenError::InvalidPlacement(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6k white - -"), Some(FenError::InvalidSideToMove(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6k w KK -"), Some(FenError::InvalidCastling(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6k w - e9"), Some(FenError::InvalidEnPassant(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6k w - e4"), Some(FenError::InvalidEnPassant(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6k w - e3"), Some(FenError::ImplausibleEnPassant(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6k w - e6"), Some(FenError::ImplausibleEnPassant(_))));
+        assert!(matches!(err("8/8/8/8/8/8/8/K6k w - - x 1"), Some(FenError::InvalidCounter(_))));
+        assert_eq!(err("8/8/8/8/8/8/8/K7 w - -"), Some(FenError::KingCount(Color::Black, 0)));
+        assert_eq!(err("8/8/8/8/8/8/8/KK5k w - -"), Some(FenError::KingCount(Color::White, 2)));
+        assert_eq!(err("QQQQQQQQ/QQ6/8/8/8/8/8/K6k w - -"), Some(FenError::TooManyPices(Color::White)));
+        assert_eq!(err("P7/8/8/8/8/8/8/K6k w - -"), Some(FenError::PawnOnBackRank(56)));
+        assert_eq!(err("7k/8/8/8/8/8/8/K6R w - -"), Some(FenError::OpponentInCheck));
+        assert_eq!(err("4k3/8/8/8/8/8/8/4K3 w K -"), Some(FenError::CastlingWithoutRook('K')));
+        assert!(Board::try_from_fen("4k3/8/8/8/8/8/8/4K2R b K - 0 1").is_ok());
+    }
+}
@@ -1,13 +1,14 @@
 use std::fmt;
-use std::collections::HashSet;
 pub mod pice;
 pub mod state;
 pub mod color;
-mod zobrist;
+pub mod zobrist;
+pub mod fen;
 
-use crate::{movegeneration::movegenerator::MoveGenerator, board::pice::Pice, movegeneration::singlemove::{Move, MoveType}, board::{state::{CastleRights, State}, zobrist::Zobrist}, board::color::Color};
+use crate::{constants, movegeneration::movegenerator::MoveGenerator, board::pice::{Pice, PiceType}, movegeneration::singlemove::{Move, MoveType}, board::{state::{CastleRights, State}, zobrist::{HashMode, Zobrist}}, board::color::Color};
 const OLD_MOVEGEN: bool = false;
 
+#[derive(Clone)]
 pub struct Board{
     pub pices: Vec<Pice>,
     board: [Option<usize>;64],
@@ -15,55 +16,32 @@ pub struct Board{
     pub moves: Vec<Move>,
     pub state: State,
     zobrist: Zobrist,
-    game_history: HashSet<u64>,
-    in_check: bool
+    // zobrist hash of every position since the fen, the last one is the current position
+    positions: Vec<u64>,
+    in_check: bool,
+    // plies since the last capture or pawn move, the previous values are kept for undo
+    halfmove_clock: u16,
+    halfmove_history: Vec<u16>,
+    fullmove_number: u16,
+    // the en passant square from the fen, restored when the first move is undone
+    start_passant: u64
 }
 
 impl Board {
     fn new(pices: Vec<Pice>, board: [Option<usize>;64], turn: Color, state: State) -> Board{
-        let zobrist = Zobrist::from_pices(&pices, &state, turn);
-        Board { pices, board, turn, moves: vec![], state, zobrist, game_history: HashSet::new(), in_check: false }
+        let zobrist = Zobrist::from_pices(&pices, &state, turn, HashMode::Native);
+        let positions = vec![zobrist.get()];
+        let start_passant = state.passant;
+        Board { pices, board, turn, moves: vec![], state, zobrist, positions, in_check: false, halfmove_clock: 0, halfmove_history: vec![], fullmove_number: 1, start_passant }
     }
 
     pub fn default() -> Board{
         Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
     }
 
+    // for fens known to be well formed, the position isn't validated, use try_from_fen for input from outside
     pub fn from_fen(s: &str) -> Board{
-        let seq: Vec<&str> = s.split(" ").collect();
-        let mut pices: Vec<Pice> = vec![];
-        let mut board: [Option<usize>;64] = [None;64];
-        let mut i = 56;
-        for (_, c) in seq[0].char_indices(){
-            if c.is_numeric(){
-                i += c.to_digit(10).unwrap() as u8;
-            }else if c == '/' {
-                i -= 16
-            }else {
-                pices.push(Pice::from_char(c, i));
-                i+=1;
-            }
-        }
-        pices.iter().enumerate().for_each(|(idx, pice)| {
-            board[pice.pos as usize] = Some(idx);
-        });
-        let turn = Color::from_char(seq[1].chars().nth(0).unwrap());
-        let mut state = State::from_pices(&pices, 0, 0);
-        if seq[3] != "-"{
-            state.passant = 1<<Board::square_to_bitboard(seq[3])
-        }
-        state.castle_rights = CastleRights::str_to_casle_rights(seq[2]);
-
-
-        let mut res = Board::new(pices, board, turn, state);
-
-        // needed to pass test for old movegeneration whould be removed when the old movegen goes
-        // if OLD_MOVEGEN{
-            res.update_moves(res.turn.other());
-            res.update_moves(res.turn);
-        // }
-        
-        res
+        Board::parse_fen(s).unwrap_or_else(|err| panic!("invalid fen {}: {}", s, err))
     }
 
     pub fn get_pice_pos(&self, p: u8) -> Option<&Pice>{
@@ -150,8 +128,21 @@ impl Board {
         let to = mv.to();
         let from = mv.from();
 
+        // update the move counters before the board changes
+        let pawn_move = self.board[from as usize].is_some_and(|idx| self.pices[idx].pice_type() == PiceType::Pawn);
+        let capture = self.board[to as usize].is_some() || mv.move_type() == MoveType::Pessant;
+        self.halfmove_history.push(self.halfmove_clock);
+        self.halfmove_clock = if pawn_move || capture { 0 } else { self.halfmove_clock + 1 };
+        if self.turn == Color::Black{
+            self.fullmove_number += 1;
+        }
+
+        // the hash is updated incrementally, remove the old en passant and castle rights keys
+        self.zobrist.toggle_state(&self.state, self.turn);
+
         // if a pice is captured
         if let Some(pice_pos) = self.board[to as usize] {
+            self.zobrist.toggle_pice(&self.pices[pice_pos]);
             self.pices[pice_pos].capture();
             mv.capture(pice_pos);
             self.state.remove_pice(to, &self.pices[pice_pos]);
@@ -174,28 +165,36 @@ impl Board {
 
                 if to == 2{
                     if let Some(rook_idx) = self.board[0]{
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.pices[rook_idx].move_to(&mv);
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.board[3] = self.board[0];
                         self.board[0] = None;
                         self.state.move_pice(0, 3, &self.pices[rook_idx]);
                     }
                 }else if to == 6{
                     if let Some(rook_idx) = self.board[7]{
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.pices[rook_idx].move_to(&mv);
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.board[5] = self.board[7];
                         self.board[7] = None;
                         self.state.move_pice(7, 5, &self.pices[rook_idx]);
                     }
                 }else if to == 58{
                     if let Some(rook_idx) = self.board[56]{
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.pices[rook_idx].move_to(&mv);
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.board[59] = self.board[56];
                         self.board[56] = None;
                         self.state.move_pice(56, 59, &self.pices[rook_idx]);
                     }
                 }else if to == 62{
                     if let Some(rook_idx) = self.board[63]{
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.pices[rook_idx].move_to(&mv);
+                        self.zobrist.toggle_pice(&self.pices[rook_idx]);
                         self.board[61] = self.board[63];
                         self.board[63] = None;
                         self.state.move_pice(63, 61, &self.pices[rook_idx]);
@@ -210,7 +209,8 @@ impl Board {
                 let delta:i8 = if to > from { -8 } else { 8 };
                 
                 let pice_pos = self.board[(to as i8 + delta) as usize].unwrap();
-                self.pices[pice_pos].capture();
+                self.zobrist.toggle_pice(&self.pices[pice_pos]);
+            self.pices[pice_pos].capture();
                 mv.capture(pice_pos);
                 self.board[(to as i8 + delta) as usize] = None;
                 self.state.remove_pice((to as i8 + delta) as u8, &self.pices[pice_pos]);
@@ -250,6 +250,9 @@ impl Board {
             self.update_moves(self.turn);
         }
         self.turn = self.turn.other();
+        self.zobrist.toggle_state(&self.state, self.turn);
+        self.zobrist.toggle_turn();
+        self.push_position();
     }
 
     pub fn undo_last_move(&mut self){
@@ -328,11 +331,13 @@ impl Board {
                 }
             }
 
-            // reinstate castlerights
-            if let Some(last_move) = self.moves.last(){
-                if last_move.move_type() == MoveType::Pawndubblemove{
+            // reinstate en passant
+            match self.moves.last() {
+                Some(last_move) if last_move.move_type() == MoveType::Pawndubblemove => {
                     self.state.passant = 1<<((last_move.from() + last_move.to())/2);
-                }
+                },
+                None => self.state.passant = self.start_passant,
+                _ => {}
             }
         }
         
@@ -344,12 +349,22 @@ impl Board {
             self.update_moves(self.turn);
         }
 
+        if let Some(clock) = self.halfmove_history.pop(){
+            self.halfmove_clock = clock;
+            if self.turn == Color::Black{
+                self.fullmove_number -= 1;
+            }
+        }
+        self.pop_position();
+
 
     }
 
     fn move_pice(&mut self, mv: Move){
         if let Some(pice_pos) = self.board[mv.from() as usize] {
+            self.zobrist.toggle_pice(&self.pices[pice_pos]);
             self.pices[pice_pos].move_to(&mv);
+            self.zobrist.toggle_pice(&self.pices[pice_pos]);
             self.board[mv.to() as usize] = self.board[mv.from() as usize];
             self.board[mv.from() as usize] = None;
             self.state.move_pice(mv.from(), mv.to(), &self.pices[pice_pos]);
@@ -359,7 +374,9 @@ impl Board {
     fn move_pice_promotion(&mut self, mv: Move){
         if let Some(pice_pos) = self.board[mv.from() as usize] {
             self.state.remove_pice( mv.from(), &self.pices[pice_pos]);
+            self.zobrist.toggle_pice(&self.pices[pice_pos]);
             self.pices[pice_pos].move_to(&mv);
+            self.zobrist.toggle_pice(&self.pices[pice_pos]);
             self.board[mv.to() as usize] = self.board[mv.from() as usize];
             self.board[mv.from() as usize] = None;
             self.state.reinstate_pice( mv.to(), &self.pices[pice_pos]);
@@ -386,6 +403,7 @@ impl Board {
         }
     }
 
+    #[allow(dead_code)]
     fn square_to_bitboard(pos: &str) -> u8{
         let mut bitboard: u8 = 0;
         bitboard += pos.chars().nth(0).unwrap() as u8 - 'a' as u8;
@@ -393,11 +411,40 @@ impl Board {
 
     }
 
-    pub fn get_zobrist_hash(&mut self) -> u64{
-        self.zobrist = Zobrist::from_pices(&self.pices, &self.state, self.turn);
+    pub fn get_zobrist_hash(&self) -> u64{
         self.zobrist.get()
     }
 
+    // the key used by polyglot opening books, independent of the hash mode of the board
+    pub fn polyglot_hash(&self) -> u64{
+        Zobrist::from_pices(&self.pices, &self.state, self.turn, HashMode::Polyglot).get()
+    }
+
+    // rehash the current position and the game history with other random keys
+    #[allow(dead_code)]
+    pub fn set_hash_mode(&mut self, mode: HashMode){
+        let moves = self.moves.clone();
+        moves.iter().for_each(|_| self.undo_last_move());
+        self.zobrist = Zobrist::from_pices(&self.pices, &self.state, self.turn, mode);
+        self.positions = vec![self.zobrist.get()];
+        for mv in moves{
+            self.make_move(mv);
+        }
+    }
+
+    fn push_position(&mut self){
+        debug_assert_eq!(self.zobrist.get(), Zobrist::from_pices(&self.pices, &self.state, self.turn, self.zobrist.mode()).get(), "incremental zobrist hash out of sync");
+        self.positions.push(self.zobrist.get());
+    }
+
+    // the previous hash is taken from the position stack instead of undoing every change
+    fn pop_position(&mut self){
+        if self.positions.len() > 1{
+            self.positions.pop();
+        }
+        self.zobrist.set(self.positions[self.positions.len() - 1]);
+    }
+
     // pub fn is_white(&self) -> bool{
     //     self.turn == Color::White
     // }
@@ -406,13 +453,52 @@ impl Board {
         self.turn
     }
 
-    pub fn add_state_to_history(&mut self){
-        let zob = self.get_zobrist_hash();
-        self.game_history.insert(zob);
+    // earlier occurrences of the current position, only positions since the last capture or pawn move can repeat
+    fn repetitions(&self) -> impl Iterator<Item = usize> + '_{
+        let current = self.zobrist.get();
+        let last = self.positions.len() - 1;
+        let window = (self.halfmove_clock as usize).min(last);
+        (2..=window).step_by(2).filter(move |&back| self.positions[last - back] == current)
     }
 
-    pub fn game_history_contains(&self, zobrist: u64) -> bool{
-        self.game_history.contains(&zobrist)
+    #[allow(dead_code)]
+    pub fn is_threefold_repetition(&self) -> bool{
+        self.repetitions().count() >= 2
+    }
+
+    // used by the search, a repetition inside the searched line is enough since it can be repeated again,
+    // positions from the game before the root have to occur three times
+    pub fn is_repetition(&self, ply: usize) -> bool{
+        let mut count = 0;
+        for back in self.repetitions(){
+            if back <= ply{
+                return true;
+            }
+            count += 1;
+        }
+        count >= 2
+    }
+
+    // checkmate on the last move takes precedence over the fifty move rule
+    pub fn is_fifty_move_draw(&mut self) -> bool{
+        if self.halfmove_clock < 100{
+            return false;
+        }
+        !(self.get_possible_moves_turn().is_empty() && self.in_check())
+    }
+
+    // no sequence of legal moves can lead to mate: K v K, K+minor v K or only bishops on the same square color
+    pub fn is_insufficient_material(&self) -> bool{
+        let (white, black) = (&self.state.white, &self.state.black);
+        if (white.pawns | black.pawns | white.orthoganal_sliders | black.orthoganal_sliders) != 0{
+            return false;
+        }
+        let knights = white.knights | black.knights;
+        let bishops = white.diagonal_sliders | black.diagonal_sliders;
+        if (knights | bishops).count_ones() <= 1{
+            return true;
+        }
+        knights == 0 && (bishops & constants::LIGHT_SQUARES == 0 || bishops & !constants::LIGHT_SQUARES == 0)
     }
 
     pub fn in_check(&self) -> bool{
@@ -433,23 +519,140 @@ impl Board {
         None
     }
 
+    // standard algebraic notation like Nbd7, exd5, e8=Q+ or O-O, check and annotation suffixes are ignored
+    pub fn move_from_san(&mut self, san: &str) -> Option<Move>{
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let moves = self.get_possible_moves_turn();
+        if san == "O-O" || san == "0-0" || san == "O-O-O" || san == "0-0-0" {
+            let file = if san.len() == 3 { 6 } else { 2 };
+            return moves.into_iter().find(|mv| mv.move_type() == MoveType::Castle && mv.to() % 8 == file);
+        }
+        let (san, promotion) = match san.find('=') {
+            Some(idx) => (&san[..idx], san[idx + 1..].chars().next()),
+            None => match san.chars().last() {
+                Some(c) if "QRBN".contains(c) => (&san[..san.len() - 1], Some(c)),
+                _ => (san, None)
+            }
+        };
+        let promotion = match promotion.map(|c| c.to_ascii_uppercase()) {
+            Some('Q') => Some(MoveType::PromotionQueen),
+            Some('R') => Some(MoveType::PromotionRook),
+            Some('B') => Some(MoveType::PromotionBishop),
+            Some('N') => Some(MoveType::PromotionKnight),
+            Some(_) => return None,
+            None => None
+        };
+        let (pice_type, rest) = match san.chars().next() {
+            Some(c) if "KQRBN".contains(c) => (PiceType::from_char(c), &san[1..]),
+            _ => (PiceType::Pawn, san)
+        };
+        let rest = rest.replace('x', "");
+        if rest.len() < 2 || !rest.is_ascii() {
+            return None;
+        }
+        let (disambiguation, destination) = rest.split_at(rest.len() - 2);
+        let to = Board::parse_square(destination)?;
+        moves.into_iter().find(|mv| {
+            let from = mv.from();
+            mv.to() == to
+                && self.get_pice_pos(from).map(|pice| pice.pice_type()) == Some(pice_type)
+                && disambiguation.chars().all(|c| match c {
+                    'a'..='h' => from % 8 == c as u8 - b'a',
+                    '1'..='8' => from / 8 == c as u8 - b'1',
+                    _ => false
+                })
+                && match promotion {
+                    Some(typ) => mv.move_type() == typ,
+                    None => !mv.move_type().is_promotion()
+                }
+        })
+    }
+
+    // the standard algebraic notation of a legal move, with file or rank added when another pice can reach the same square
+    pub fn san(&mut self, mv: Move) -> String{
+        let moves = self.get_possible_moves_turn();
+        let coordinates = mv.long_algebraic_notation();
+        let mut res = String::new();
+        if mv.move_type() == MoveType::Castle {
+            res.push_str(if mv.to() % 8 == 6 { "O-O" } else { "O-O-O" });
+        }else {
+            let pice_type = self.get_pice_pos(mv.from()).map(|pice| pice.pice_type()).expect("no pice to move");
+            let capture = self.get_pice_pos(mv.to()).is_some() || mv.move_type() == MoveType::Pessant;
+            if pice_type == PiceType::Pawn {
+                if capture {
+                    res.push_str(&coordinates[0..1]);
+                }
+            }else {
+                res.push_str(&PiceType::char(pice_type as u8).to_uppercase());
+                let others: Vec<u8> = moves.iter()
+                    .filter(|other| other.to() == mv.to() && other.from() != mv.from())
+                    .filter(|other| self.get_pice_pos(other.from()).map(|pice| pice.pice_type()) == Some(pice_type))
+                    .map(|other| other.from())
+                    .collect();
+                if !others.is_empty() {
+                    if others.iter().all(|from| from % 8 != mv.from() % 8) {
+                        res.push_str(&coordinates[0..1]);
+                    }else if others.iter().all(|from| from / 8 != mv.from() / 8) {
+                        res.push_str(&coordinates[1..2]);
+                    }else {
+                        res.push_str(&coordinates[0..2]);
+                    }
+                }
+            }
+            if capture {
+                res.push('x');
+            }
+            res.push_str(&coordinates[2..4]);
+            if mv.move_type().is_promotion() {
+                res.push('=');
+                res.push_str(&coordinates[4..].to_uppercase());
+            }
+        }
+        let in_check = self.in_check;
+        self.make_move(mv);
+        let replies = self.get_possible_moves_turn();
+        if self.in_check() {
+            res.push(if replies.is_empty() { '#' } else { '+' });
+        }
+        self.undo_last_move();
+        self.in_check = in_check;
+        res
+    }
+
+    fn parse_square(square: &str) -> Option<u8>{
+        let bytes = square.as_bytes();
+        match bytes {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Some((rank - b'1') * 8 + file - b'a'),
+            _ => None
+        }
+    }
+
     pub fn make_null_move(&mut self){
+        self.halfmove_history.push(self.halfmove_clock);
+        self.halfmove_clock += 1;
+        self.zobrist.toggle_state(&self.state, self.turn);
         self.state.passant = 0;
+        self.zobrist.toggle_state(&self.state, self.turn);
+        self.zobrist.toggle_turn();
         self.turn = self.turn.other();
         self.moves.push(Move::null_move());
+        self.push_position();
     }
 
     pub fn undo_null_move(&mut self){
+        if let Some(clock) = self.halfmove_history.pop(){
+            self.halfmove_clock = clock;
+        }
         self.moves.pop();
         self.turn = self.turn.other();
-        if let Some(mv) = self.moves.last(){
-            match mv.move_type() {
-                MoveType::Pawndubblemove => {
-                    self.state.passant = 1<<((mv.from() + mv.to())/2);
-                },
-                _ => {}
-            }
+        match self.moves.last() {
+            Some(mv) if mv.move_type() == MoveType::Pawndubblemove => {
+                self.state.passant = 1<<((mv.from() + mv.to())/2);
+            },
+            None => self.state.passant = self.start_passant,
+            _ => {}
         }
+        self.pop_position();
     }
 
     #[allow(dead_code)]
@@ -460,6 +663,14 @@ impl Board {
     pub fn is_white_move(&self) -> bool{
         self.turn == Color::White
     }
+
+    pub fn halfmove_clock(&self) -> u16{
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u16{
+        self.fullmove_number
+    }
 }
 
 impl fmt::Display for Board {
@@ -481,7 +692,8 @@ impl fmt::Display for Board {
             }
             writeln!(f)?;
         }
-        writeln!(f, "Turn: {:?}", self.turn)?;
+        writeln!(f, "Turn: {:?}, move: {}, halfmove clock: {}", self.turn, self.fullmove_number(), self.halfmove_clock())?;
+        writeln!(f, "Fen: {}", self.to_fen())?;
         Ok(())
     }
 }
@@ -490,7 +702,7 @@ impl fmt::Display for Board {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::{board::Board, board::pice::{PiceType, Pice}, movegeneration::singlemove::{Move, MoveType}, board::color::Color};
+    use crate::{board::Board, board::pice::{PiceType, Pice}, movegeneration::{perft::{perft, perft_hash}, singlemove::{Move, MoveType}}, board::color::Color};
 
     #[test]
     fn fen_default() {
@@ -536,6 +748,138 @@ mod tests {
         assert_eq!(b.state.passant, 1<<22);
     }
 
+    #[test]
+    fn from_fen_move_counters() {
+        let b: Board = Board::from_fen("rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        assert_eq!(b.halfmove_clock(), 1);
+        assert_eq!(b.fullmove_number(), 2);
+        let b: Board = Board::from_fen("rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq -");
+        assert_eq!(b.halfmove_clock(), 0);
+        assert_eq!(b.fullmove_number(), 1);
+    }
+
+    #[test]
+    fn make_and_undo_move_counters() {
+        let mut b: Board = Board::from_fen("rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        b.make_move(Move::new(57, 42, MoveType::Normal));
+        assert_eq!((b.halfmove_clock(), b.fullmove_number()), (2, 3));
+        b.make_move(Move::new(5, 26, MoveType::Normal));
+        assert_eq!((b.halfmove_clock(), b.fullmove_number()), (3, 3));
+        b.make_move(Move::new(51, 43, MoveType::Normal));
+        assert_eq!((b.halfmove_clock(), b.fullmove_number()), (0, 4));
+        b.undo_last_move();
+        assert_eq!((b.halfmove_clock(), b.fullmove_number()), (3, 3));
+        b.undo_last_move();
+        b.undo_last_move();
+        assert_eq!((b.halfmove_clock(), b.fullmove_number()), (1, 2));
+    }
+
+    #[test]
+    fn incremental_zobrist_transposition() {
+        let mut a = Board::default();
+        let mut b = Board::default();
+        for mv in [Move::new(6, 21, MoveType::Normal), Move::new(57, 42, MoveType::Normal), Move::new(1, 18, MoveType::Normal)]{
+            a.make_move(mv);
+        }
+        for mv in [Move::new(1, 18, MoveType::Normal), Move::new(57, 42, MoveType::Normal), Move::new(6, 21, MoveType::Normal)]{
+            b.make_move(mv);
+        }
+        let fen = Board::from_fen("r1bqkbnr/pppppppp/2n5/8/8/2N2N2/PPPPPPPP/R1BQKB1R b KQkq - 3 2");
+        assert_eq!(a.get_zobrist_hash(), b.get_zobrist_hash());
+        assert_eq!(a.get_zobrist_hash(), fen.get_zobrist_hash());
+        a.undo_last_move();
+        a.undo_last_move();
+        a.undo_last_move();
+        assert_eq!(a.get_zobrist_hash(), Board::default().get_zobrist_hash());
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        let mut b = Board::default();
+        let shuffle = [Move::new(6, 21, MoveType::Normal), Move::new(62, 45, MoveType::Normal), Move::new(21, 6, MoveType::Normal), Move::new(45, 62, MoveType::Normal)];
+        for mv in shuffle{
+            b.make_move(mv);
+        }
+        // the start position has occurred twice
+        assert!(!b.is_threefold_repetition());
+        assert!(b.is_repetition(4));
+        assert!(!b.is_repetition(3));
+        for mv in shuffle{
+            b.make_move(mv);
+        }
+        assert!(b.is_threefold_repetition());
+        assert!(b.is_repetition(0));
+        b.undo_last_move();
+        assert!(!b.is_threefold_repetition());
+    }
+
+    #[test]
+    fn fifty_move_draw() {
+        let mut b = Board::from_fen("7k/8/8/8/8/8/8/K6Q b - - 100 120");
+        assert!(b.is_fifty_move_draw());
+        let mut b = Board::from_fen("7k/8/8/8/8/8/8/K6Q b - - 99 120");
+        assert!(!b.is_fifty_move_draw());
+        // checkmate on the hundredth ply is still checkmate
+        let mut b = Board::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 100 120");
+        assert!(!b.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn insufficient_material() {
+        assert!(Board::from_fen("7k/8/8/8/8/8/8/K7 w - - 0 1").is_insufficient_material());
+        assert!(Board::from_fen("7k/8/8/8/8/8/8/KN6 w - - 0 1").is_insufficient_material());
+        assert!(Board::from_fen("7k/8/8/8/8/8/8/KB3b2 w - - 0 1").is_insufficient_material());
+        assert!(!Board::from_fen("7k/8/8/8/8/8/8/KB2b3 w - - 0 1").is_insufficient_material());
+        assert!(!Board::from_fen("7k/8/8/8/8/8/8/KNN5 w - - 0 1").is_insufficient_material());
+        assert!(!Board::from_fen("7k/8/8/8/8/8/1P6/K7 w - - 0 1").is_insufficient_material());
+    }
+
+    #[test]
+    fn move_from_san() {
+        let mut b = Board::default();
+        let san = |b: &mut Board, s: &str| b.move_from_san(s).map(|mv| mv.long_algebraic_notation());
+        assert_eq!(san(&mut b, "e4"), Some("e2e4".into()));
+        assert_eq!(san(&mut b, "Nf3!"), Some("g1f3".into()));
+        assert_eq!(san(&mut b, "Ke2"), None);
+
+        let mut b = Board::from_fen("r3k2r/1P6/8/3p4/4P3/8/8/RN2K1NR w KQkq - 0 1");
+        assert_eq!(san(&mut b, "exd5"), Some("e4d5".into()));
+        assert_eq!(san(&mut b, "bxa8=N+"), Some("b7a8n".into()));
+        assert_eq!(san(&mut b, "b8Q"), Some("b7b8q".into()));
+        assert_eq!(san(&mut b, "O-O"), None);
+        assert_eq!(san(&mut b, "Ngf3"), Some("g1f3".into()));
+
+        let mut b = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1");
+        assert_eq!(san(&mut b, "Nbd2"), Some("b1d2".into()));
+        assert_eq!(san(&mut b, "Nfd2"), Some("f1d2".into()));
+
+        let mut b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+        assert_eq!(san(&mut b, "O-O-O"), Some("e8c8".into()));
+        assert_eq!(san(&mut b, "0-0"), Some("e8g8".into()));
+    }
+
+    #[test]
+    fn san() {
+        let cases = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "g1f3", "Nf3"),
+            ("r3k2r/1P6/8/3p4/4P3/8/8/RN2K1NR w KQkq - 0 1", "e4d5", "exd5"),
+            ("r3k2r/1P6/8/3p4/4P3/8/8/RN2K1NR w KQkq - 0 1", "b7a8q", "bxa8=Q+"),
+            ("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "b1d2", "Nbd2"),
+            ("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "e1d2", "Kd2"),
+            ("4k3/8/8/N7/8/8/8/N3K3 w - - 0 1", "a1b3", "N1b3"),
+            ("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "a1b2", "Qa1b2"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8c8", "O-O-O"),
+            ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#"),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", "exd6"),
+        ];
+        for (fen, long, expected) in cases{
+            let mut b = Board::from_fen(fen);
+            let mv = b.move_from_long_algebraic_notation(long.into()).unwrap();
+            assert_eq!(b.san(mv), expected);
+            assert_eq!(b.move_from_san(expected).map(|mv| mv.long_algebraic_notation()), Some(long.into()));
+        }
+    }
+
     #[allow(non_snake_case)]
     #[test]
     fn from_fen_casle_rights_KQkq() {
@@ -576,41 +920,41 @@ mod tests {
     #[test]
     fn count_moves_one_move() {
         let mut board = Board::from_fen("r1bqkbnr/pppppppp/n7/8/8/P7/1PPPPPPP/RNBQKBNR w KQkq - 1 2");
-        assert_eq!(count_moves(&mut board, 2), 380);
+        assert_eq!(perft(&mut board, 2), 380);
     }
 
     #[test]
     fn count_moves_one_move_2() {
         let mut board = Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/8/2N5/PPPPPPPP/R1BQKBNR w KQkq - 0 2");
-        assert_eq!(count_moves_print(&mut board, 2, 1), 656);
+        assert_eq!(perft(&mut board, 2), 656);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_default_1() {
         let mut board = Board::default();
-        assert_eq!(count_moves(&mut board, 1), 20);
-        assert_eq!(count_moves(&mut board, 2), 400);
-        assert_eq!(count_moves(&mut board, 3), 8_902);
-        assert_eq!(count_moves(&mut board, 4), 197_281);
-        assert_eq!(count_moves(&mut board, 5), 4_865_609);
-        assert_eq!(count_moves(&mut board, 6), 119_060_324);
-        // assert_eq!(count_moves(&mut board, 7), 3_195_901_860);
-        // assert_eq!(count_moves(&mut board, 8), 84_998_978_956);
+        assert_eq!(perft(&mut board, 1), 20);
+        assert_eq!(perft(&mut board, 2), 400);
+        assert_eq!(perft(&mut board, 3), 8_902);
+        assert_eq!(perft(&mut board, 4), 197_281);
+        assert_eq!(perft(&mut board, 5), 4_865_609);
+        assert_eq!(perft(&mut board, 6), 119_060_324);
+        // assert_eq!(perft(&mut board, 7), 3_195_901_860);
+        // assert_eq!(perft(&mut board, 8), 84_998_978_956);
     }
 
     #[test]
     fn count_moves_default_specific_moves() {
         // added pawn could not capture pice to get out of check
         let mut board = Board::from_fen("rnbqk1nr/pppp1ppp/8/4p3/1b1P4/P7/1PP1PPPP/RNBQKBNR w KQkq - 1 3");
-        assert_eq!(count_moves_print(&mut board, 1,1), 6);
+        assert_eq!(perft(&mut board, 1), 6);
     }
 
     #[test]
     fn count_moves_default_specific_moves_2() {
         // added pawn could not capture pice to get out of check
         let mut board = Board::from_fen("rnbqkbnr/pppp1ppp/4p3/4P3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2");
-        let count = count_moves_print(&mut board, 2,1);
+        let count = perft(&mut board, 2);
         assert_eq!(board.pices.iter().filter(|p| p.color() == Color::White).count(),board.pices.iter().filter(|p| p.color() == Color::Black).count());
         assert_eq!(count, 840);
     }
@@ -620,7 +964,7 @@ mod tests {
         // added pawn could not capture pice to get out of check
         let mut board = Board::from_fen("rnbqkbnr/pppp1ppp/4p3/4P3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2");
         board.make_move(Move::new(55, 39, MoveType::Pawndubblemove));
-        assert_eq!(count_moves_print(&mut board, 1,1), 29);
+        assert_eq!(perft(&mut board, 1), 29);
     }
 
     #[test]
@@ -635,24 +979,24 @@ mod tests {
     #[ignore = "large movegeneration takes time"]
     fn count_moves_kiwipete_small() {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
-        assert_eq!(count_moves(&mut board, 1), 48);
-        assert_eq!(count_moves(&mut board, 2), 2_039);
-        assert_eq!(count_moves(&mut board, 3), 97862);
+        assert_eq!(perft(&mut board, 1), 48);
+        assert_eq!(perft(&mut board, 2), 2_039);
+        assert_eq!(perft(&mut board, 3), 97862);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_kiwipete_medium() {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
-        assert_eq!(count_moves(&mut board, 4), 4_085_603); // ca 45 sekunder
+        assert_eq!(perft(&mut board, 4), 4_085_603); // ca 45 sekunder
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_kiwipete_large() {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
-        assert_eq!(count_moves(&mut board, 5), 193_690_690);
-        assert_eq!(count_moves(&mut board, 6), 8_031_647_685);
+        assert_eq!(perft(&mut board, 5), 193_690_690);
+        assert_eq!(perft(&mut board, 6), 8_031_647_685);
     }
 
 
@@ -660,7 +1004,7 @@ mod tests {
     fn count_moves_kiwipete_move_blocks_castle() {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
         board.make_move(Move::new(36, 42, MoveType::Normal));
-        assert_eq!(count_moves(&mut board, 1),41);
+        assert_eq!(perft(&mut board, 1),41);
     }
 
     #[test]
@@ -668,7 +1012,7 @@ mod tests {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/1PN2Q1p/P1PBBPPP/R3K2R b KQkq - 0 1");
         board.make_move(Move::new(23, 14, MoveType::Normal));
         board.make_move(Move::new(12, 5, MoveType::Normal));
-        assert_eq!(count_moves(&mut board, 1),56);
+        assert_eq!(perft(&mut board, 1),56);
     }
 
     #[test]
@@ -676,7 +1020,7 @@ mod tests {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
         board.make_move(Move::new(18, 1, MoveType::Normal));
         board.make_move(Move::new(45, 28, MoveType::Normal));
-        assert_eq!(count_moves(&mut board, 1), 51);
+        assert_eq!(perft(&mut board, 1), 51);
     }
 
     #[test]
@@ -684,99 +1028,99 @@ mod tests {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
         board.make_move(Move::new(11, 29, MoveType::Normal));
         board.make_move(Move::new(23, 14, MoveType::Normal));
-        assert_eq!(count_moves(&mut board, 1), 45);
+        assert_eq!(perft(&mut board, 1), 45);
     }
 
     #[test]
     fn count_moves_pos_3_passant_double_pinned_small() {
         let mut board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ");
-        assert_eq!(count_moves(&mut board, 1), 14);
-        assert_eq!(count_moves(&mut board, 2), 191);
-        assert_eq!(count_moves(&mut board, 3), 2_812);
-        assert_eq!(count_moves(&mut board, 4), 43_238);
-        assert_eq!(count_moves(&mut board, 5), 674_624);
+        assert_eq!(perft(&mut board, 1), 14);
+        assert_eq!(perft(&mut board, 2), 191);
+        assert_eq!(perft(&mut board, 3), 2_812);
+        assert_eq!(perft(&mut board, 4), 43_238);
+        assert_eq!(perft(&mut board, 5), 674_624);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_3_passant_double_pinned_medium() {
         let mut board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ");
-        assert_eq!(count_moves(&mut board, 6), 11_030_083); // ca 45 sekunder
+        assert_eq!(perft(&mut board, 6), 11_030_083); // ca 45 sekunder
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_3_passant_double_pinned_large() {
         let mut board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ");
-        assert_eq!(count_moves(&mut board, 7), 178_633_661);
-        assert_eq!(count_moves(&mut board, 8), 3_009_794_393);
+        assert_eq!(perft(&mut board, 7), 178_633_661);
+        assert_eq!(perft(&mut board, 8), 3_009_794_393);
     }
 
     #[test]
     fn count_moves_pos_4_small() {
         let mut board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
-        assert_eq!(count_moves(&mut board, 1), 6);
-        assert_eq!(count_moves(&mut board, 2), 264);
-        assert_eq!(count_moves(&mut board, 3), 9_467);
-        assert_eq!(count_moves(&mut board, 4), 422_333);
+        assert_eq!(perft(&mut board, 1), 6);
+        assert_eq!(perft(&mut board, 2), 264);
+        assert_eq!(perft(&mut board, 3), 9_467);
+        assert_eq!(perft(&mut board, 4), 422_333);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_4_medium() {
         let mut board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
-        assert_eq!(count_moves(&mut board, 5), 15_833_292);
+        assert_eq!(perft(&mut board, 5), 15_833_292);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_4_large() {
         let mut board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
-        assert_eq!(count_moves(&mut board, 6), 706_045_033);
+        assert_eq!(perft(&mut board, 6), 706_045_033);
     }
 
     #[test]
     fn count_moves_pos_5_small() {
         let mut board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
-        assert_eq!(count_moves(&mut board, 1), 44);
-        assert_eq!(count_moves_print(&mut board, 2,1), 1_486);
-        assert_eq!(count_moves(&mut board, 3), 62_379);
+        assert_eq!(perft(&mut board, 1), 44);
+        assert_eq!(perft(&mut board, 2), 1_486);
+        assert_eq!(perft(&mut board, 3), 62_379);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_5_medium() {
         let mut board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8 ");
-        assert_eq!(count_moves(&mut board, 4), 2_103_487); // 116 sekunder
+        assert_eq!(perft(&mut board, 4), 2_103_487); // 116 sekunder
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_5_large() {
         let mut board = Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8 ");
-        assert_eq!(count_moves(&mut board, 5), 89_941_194); 
+        assert_eq!(perft(&mut board, 5), 89_941_194); 
     }
 
     #[test]
     fn count_moves_pos_6_small() {
         let mut board = Board::from_fen("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10");
-        assert_eq!(count_moves(&mut board, 1), 46);
-        assert_eq!(count_moves(&mut board, 2), 2_079);
-        assert_eq!(count_moves(&mut board, 3), 89_890);
+        assert_eq!(perft(&mut board, 1), 46);
+        assert_eq!(perft(&mut board, 2), 2_079);
+        assert_eq!(perft(&mut board, 3), 89_890);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_6_medium() {
         let mut board = Board::from_fen("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10 ");
-        assert_eq!(count_moves(&mut board, 4), 3_894_594); 
+        assert_eq!(perft(&mut board, 4), 3_894_594); 
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_pos_6_large() {
         let mut board = Board::from_fen("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10 ");
-        assert_eq!(count_moves(&mut board, 5), 164_075_551); 
+        assert_eq!(perft(&mut board, 5), 164_075_551); 
     }
 
     
@@ -785,35 +1129,35 @@ mod tests {
         let mut board = Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
         board.make_move(Move::new(11, 27, MoveType::Normal));
         board.make_move(Move::new(9, 0, MoveType::PromotionQueen));
-        assert_eq!(count_moves_print(&mut board, 1,1), 39);
+        assert_eq!(perft(&mut board, 1), 39);
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_kiwipete_medium_hash_speedup() {
         let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
-        let mut hashtable: HashMap<u64, u64> = HashMap::new();
-        assert_eq!(count_moves_hash(&mut board, &mut hashtable, 4), 4_085_603); // ca 45 sekunder utan hash 25 med
+        let mut hashtable = HashMap::new();
+        assert_eq!(perft_hash(&mut board, &mut hashtable, 4), 4_085_603); // ca 45 sekunder utan hash 25 med
     }
 
     #[test]
     #[ignore = "large movegeneration takes time"]
     fn count_moves_default_hash_speedup() {
         let mut board = Board::default();
-        let mut hashtable: HashMap<u64, u64> = HashMap::new();
+        let mut hashtable = HashMap::new();
         
-        assert_eq!(count_moves_hash(&mut board, &mut hashtable, 1), 20);
-        assert_eq!(count_moves_hash(&mut board, &mut hashtable, 2), 400);
-        assert_eq!(count_moves_hash(&mut board, &mut hashtable, 3), 8_902);
+        assert_eq!(perft_hash(&mut board, &mut hashtable, 1), 20);
+        assert_eq!(perft_hash(&mut board, &mut hashtable, 2), 400);
+        assert_eq!(perft_hash(&mut board, &mut hashtable, 3), 8_902);
         hashtable = HashMap::new();
-        assert_eq!(count_moves_hash(&mut board, &mut hashtable, 4), 197_281);
+        assert_eq!(perft_hash(&mut board, &mut hashtable, 4), 197_281);
         hashtable = HashMap::new();
-        assert_eq!(count_moves_hash(&mut board, &mut hashtable, 5), 4_865_609);
+        assert_eq!(perft_hash(&mut board, &mut hashtable, 5), 4_865_609);
         // hashtable = HashMap::new();
-        // assert_eq!(count_moves_hash(&mut board, &mut hashtable, 6), 119_060_324);
+        // assert_eq!(perft_hash(&mut board, &mut hashtable, 6), 119_060_324);
 
-        // assert_eq!(count_moves(&mut board, 7), 3_195_901_860);
-        // assert_eq!(count_moves(&mut board, 8), 84_998_978_956);
+        // assert_eq!(perft(&mut board, 7), 3_195_901_860);
+        // assert_eq!(perft(&mut board, 8), 84_998_978_956);
     }
 
     #[test]
@@ -887,81 +1231,6 @@ mod tests {
         assert_eq!(moves.len(), 4);
     }
 
-    fn count_moves(board: &mut Board, depth: u8) -> u64{
-        if depth == 0{ 
-            // println!("{}", board);
-            // assert_eq!(board.pices.iter().filter(|p| p.color() == Color::White).count(),board.pices.iter().filter(|p| p.color() == Color::Black).count());
-            
-            return 1;
-        }
-        // let mut mg = MoveGenerator::new(&board);
-        // let moves = mg.gen_moves_turn(board, false);
-        // println!("moves: {:?}", moves);
-        let moves = board.get_possible_moves(board.turn);
-
-        // let nbr_castle = moves.iter().filter(|mv| mv.move_type() == MoveType::Castle).count();
-        // println!("nbr castle {}", nbr_castle);
-
-        let mut res = 0;
-        for m in moves{
-            board.make_move(m);
-            res += count_moves(board, depth - 1);
-            board.undo_last_move();
-        }
-        res
-    }
-
-    fn count_moves_print(board: &mut Board, depth: u8, print_depth: i32) -> u64{
-        if depth == 0{ 
-            // println!("{}", board);
-            return 1;
-        }
-        // let mut mg = MoveGenerator::new(&board);
-        // let moves = mg.gen_moves_turn(board, false);
-
-        let moves = board.get_possible_moves(board.turn);
-        // let nbr_castle = moves.iter().filter(|mv| mv.move_type() == MoveType::Castle).count();
-        // println!("nbr castle {}", nbr_castle);
-        let mut res = 0;
-        for m in moves{
-            board.make_move(m);
-            // println!("{}",board);
-
-            let a = count_moves(board, depth - 1);
-            if print_depth > 0{
-                println!("from: {}, to {} : {}, typ: {:?}", m.from(), m.to(), a, m.move_type());
-            }
-            res += a;
-            board.undo_last_move();
-        }
-        res
-    }
-
-    fn count_moves_hash(board: &mut Board, hashtable: &mut HashMap<u64, u64>, depth: u8) -> u64{
-        if depth == 0{ 
-            // println!("{}", board);
-            // assert_eq!(board.pices.iter().filter(|p| p.color() == Color::White).count(),board.pices.iter().filter(|p| p.color() == Color::Black).count());
-
-            return 1;
-        }
-        if let Some(nbr_moves) = hashtable.get(&(board.get_zobrist_hash() ^ (depth as u64))) {
-            return *nbr_moves;
-        }
-        let moves = board.get_possible_moves(board.turn);
-
-        // let nbr_castle = moves.iter().filter(|mv| mv.move_type() == MoveType::Castle).count();
-        // println!("nbr castle {}", nbr_castle);
-
-        let mut res = 0;
-        for m in moves{
-            board.make_move(m);
-            res += count_moves_hash(board, hashtable ,depth - 1);
-            board.undo_last_move();
-        }
-        hashtable.insert(board.get_zobrist_hash() ^ (depth as u64), res);
-        res
-    }
-
     fn get_set_bits(&pos: &u64) -> Vec<u8>{
         let mut i = pos.clone();
         let mut res = vec![];
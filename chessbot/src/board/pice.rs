@@ -57,7 +57,7 @@ impl PiceType {
 }
 
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Pice{
     pub typ: u8, //xxxCCTTT C=1 if captured
     pub pos: u8, 
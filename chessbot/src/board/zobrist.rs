@@ -1,30 +1,46 @@
 use crate::constants;
 
-use super::{color::Color, pice::{Pice, PiceType}, state::State};
+use super::{color::Color, pice::{Pice, PiceType}, state::{CastleRights, State}};
 
 
 
-#[derive(Debug)]
-pub struct Zobrist(u64);
+// identifies the native keys, every key of every table goes into it in order
+pub fn key_fingerprint() -> u64{
+    let pices = [
+        constants::ZOBRIST_WHITE_PAWN, constants::ZOBRIST_WHITE_KNIGHT, constants::ZOBRIST_WHITE_BISHOP,
+        constants::ZOBRIST_WHITE_ROOK, constants::ZOBRIST_WHITE_QUEEN, constants::ZOBRIST_WHITE_KING,
+        constants::ZOBRIST_BLACK_PAWN, constants::ZOBRIST_BLACK_KNIGHT, constants::ZOBRIST_BLACK_BISHOP,
+        constants::ZOBRIST_BLACK_ROOK, constants::ZOBRIST_BLACK_QUEEN, constants::ZOBRIST_BLACK_KING
+    ];
+    pices.iter().flatten()
+        .chain(constants::ZOBRIST_CASTLE_RIGHTS.iter())
+        .chain(constants::ZOBRIST_PASSANT_FILE.iter())
+        .chain(constants::ZOBRIST_TURN_COLOR.iter())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, key| (hash ^ key).wrapping_mul(0x0100_0000_01b3).rotate_left(29))
+}
+
+// native uses the engines own random keys, polyglot the keys and conventions of polyglot opening books
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashMode {
+    Native,
+    Polyglot,
+}
+
+#[derive(Debug, Clone)]
+pub struct Zobrist{
+    hash: u64,
+    mode: HashMode
+}
 
 impl Zobrist {
-    pub fn from_pices(pices: &Vec<Pice>, state: &State, turn: Color) -> Zobrist{
-        let mut zob = 0;
-        zob ^= state.passant;
-        zob ^= constants::ZOBRIST_CASTLE_RIGHTS[state.castle_rights as usize];
+    pub fn from_pices(pices: &Vec<Pice>, state: &State, turn: Color, mode: HashMode) -> Zobrist{
+        let mut zob = Zobrist{ hash: 0, mode };
         pices.iter().filter(|pice| !pice.is_captured()).for_each(|pice| {
-            zob ^= Zobrist::rand_value(&pice);
+            zob.toggle_pice(pice);
         });
-        match turn {
-            Color::White => {
-                zob ^= constants::ZOBRIST_TURN_COLOR[0];
-            },
-            Color::Black => {
-                zob ^= constants::ZOBRIST_TURN_COLOR[1];
-            }
-        }
-
-        Zobrist(zob)
+        zob.toggle_state(state, turn);
+        zob.hash ^= zob.turn_key(turn);
+        zob
     }
 
     #[allow(dead_code)]
@@ -53,7 +69,7 @@ impl Zobrist {
         zob ^= constants::ZOBRIST_BLACK_KNIGHT[62];
         zob ^= constants::ZOBRIST_BLACK_ROOK[63];
         
-        Zobrist(zob)
+        Zobrist{ hash: zob, mode: HashMode::Native }
     }
 
     fn rand_value(pice: &Pice) -> u64{
@@ -81,7 +97,131 @@ impl Zobrist {
         }
     }
 
+    // polyglot orders the pices pawn, knight, bishop, rook, queen, king with black before white
+    fn polyglot_value(pice: &Pice) -> u64{
+        let kind = match pice.pice_type() {
+            PiceType::Pawn => 0,
+            PiceType::Knight => 1,
+            PiceType::Bishop => 2,
+            PiceType::Rook => 3,
+            PiceType::Queen => 4,
+            PiceType::King => 5,
+        };
+        let white = if pice.color() == Color::White { 1 } else { 0 };
+        constants::POLYGLOT_RANDOM[64 * (2 * kind + white) + pice.pos as usize]
+    }
+
+    fn castle_key(&self, castle_rights: u8) -> u64{
+        match self.mode {
+            HashMode::Native => constants::ZOBRIST_CASTLE_RIGHTS[castle_rights as usize],
+            HashMode::Polyglot => {
+                let order = [CastleRights::WhiteKingside, CastleRights::WhiteQueenside, CastleRights::BlackKingside, CastleRights::BlackQueenside];
+                order.iter().enumerate()
+                    .filter(|(_, right)| castle_rights & (1 << (**right as u8)) != 0)
+                    .fold(0, |key, (idx, _)| key ^ constants::POLYGLOT_RANDOM[768 + idx])
+            }
+        }
+    }
+
+    // the en passant file is only hashed when a pawn of the side to move can actually capture
+    fn passant_key(&self, state: &State, turn: Color) -> u64{
+        if state.passant == 0{
+            return 0;
+        }
+        let square = state.passant.trailing_zeros() as usize;
+        let attackers = match turn {
+            Color::White => constants::BLACK_PAWN_CAPTURES[square] & state.white.pawns,
+            Color::Black => constants::WHITE_PAWN_CAPTURES[square] & state.black.pawns,
+        };
+        if attackers == 0{
+            return 0;
+        }
+        match self.mode {
+            HashMode::Native => constants::ZOBRIST_PASSANT_FILE[square % 8],
+            HashMode::Polyglot => constants::POLYGLOT_RANDOM[772 + square % 8],
+        }
+    }
+
+    fn turn_key(&self, turn: Color) -> u64{
+        match (self.mode, turn) {
+            (HashMode::Native, Color::White) => constants::ZOBRIST_TURN_COLOR[0],
+            (HashMode::Native, Color::Black) => constants::ZOBRIST_TURN_COLOR[1],
+            (HashMode::Polyglot, Color::White) => constants::POLYGLOT_RANDOM[780],
+            (HashMode::Polyglot, Color::Black) => 0,
+        }
+    }
+
+    pub fn set(&mut self, hash: u64){
+        self.hash = hash;
+    }
+
+    // xor a pice in or out of the hash, called before and after the pice changes
+    pub fn toggle_pice(&mut self, pice: &Pice){
+        self.hash ^= match self.mode {
+            HashMode::Native => Zobrist::rand_value(pice),
+            HashMode::Polyglot => Zobrist::polyglot_value(pice),
+        };
+    }
+
+    // xor the en passant and castle rights keys in or out of the hash
+    pub fn toggle_state(&mut self, state: &State, turn: Color){
+        self.hash ^= self.castle_key(state.castle_rights);
+        self.hash ^= self.passant_key(state, turn);
+    }
+
+    pub fn toggle_turn(&mut self){
+        self.hash ^= self.turn_key(Color::White) ^ self.turn_key(Color::Black);
+    }
+
     pub fn get(&self) -> u64{
-        self.0
+        self.hash
+    }
+
+    pub fn mode(&self) -> HashMode{
+        self.mode
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::board::Board;
+
+    use super::HashMode;
+
+    // reference keys from the polyglot book format description
+    #[test]
+    fn polyglot_reference_keys(){
+        let games: [(&str, u64); 9] = [
+            ("", 0x463b96181691fc9c),
+            ("e2e4", 0x823c9b50fd114196),
+            ("e2e4 d7d5", 0x0756b94461c50fb0),
+            ("e2e4 d7d5 e4e5", 0x662fafb965db29d4),
+            ("e2e4 d7d5 e4e5 f7f5", 0x22a48b5a8e47ff78),
+            ("e2e4 d7d5 e4e5 f7f5 e1e2", 0x652a607ca3f242c1),
+            ("e2e4 d7d5 e4e5 f7f5 e1e2 e8f7", 0x00fdd303c946bdd9),
+            ("a2a4 b7b5 h2h4 b5b4 c2c4", 0x3c8123ea7b067637),
+            ("a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3", 0x5c3f9b829b279560),
+        ];
+        for (moves, key) in games{
+            let mut board = Board::default();
+            board.set_hash_mode(HashMode::Polyglot);
+            for mv in moves.split_whitespace(){
+                let mv = board.move_from_long_algebraic_notation(mv.to_string()).unwrap();
+                board.make_move(mv);
+            }
+            assert_eq!(board.get_zobrist_hash(), key, "{}", moves);
+            assert_eq!(board.polyglot_hash(), key, "{}", moves);
+        }
+    }
+
+    #[test]
+    fn passant_only_hashed_when_capturable(){
+        let with_passant = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let without_passant = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(with_passant.get_zobrist_hash(), without_passant.get_zobrist_hash());
+
+        let with_passant = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        let without_passant = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3");
+        assert_ne!(with_passant.get_zobrist_hash(), without_passant.get_zobrist_hash());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,212 @@
+use std::{collections::HashMap, fs};
+
+use crate::{movegeneration::singlemove::Move, pgn::{self, PgnGame}};
+
+use super::polyglot::PolyglotBook;
+
+const DEFAULT_MAX_PLIES: usize = 16;
+const UNKNOWN_OPENING: &str = "Unknown";
+
+// results of the games a move was played in, from the perspective of the side that played it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct MoveStats{
+    games: u32,
+    wins: u32,
+    draws: u32,
+    losses: u32
+}
+
+impl MoveStats {
+    // percentage of the points the move scored, games without a result count as draws
+    fn score(&self) -> f64{
+        let undecided = self.games - self.wins - self.draws - self.losses;
+        (self.wins as f64 + (self.draws + undecided) as f64 / 2.0) * 100.0 / self.games as f64
+    }
+
+    // the weight polyglot uses, two points for a win and one for a draw
+    fn weight(&self) -> u16{
+        (2 * self.wins + self.draws).min(u16::MAX as u32) as u16
+    }
+}
+
+// collects move statistics from pgn games and writes them as an opening book
+pub struct BookBuilder{
+    max_plies: usize,
+    min_games: u32,
+    min_score: f64,
+    // polyglot hash -> moves played in that position
+    positions: HashMap<u64, Vec<(Move, MoveStats)>>,
+    // every game truncated to the ply limit with the hash of each position before the move
+    lines: Vec<(String, Vec<(u64, Move)>)>
+}
+
+impl BookBuilder {
+    pub fn new(max_plies: usize, min_games: u32, min_score: f64) -> BookBuilder{
+        BookBuilder { max_plies, min_games, min_score, positions: HashMap::new(), lines: vec![] }
+    }
+
+    // replay all games in the pgn text, games that can't be read are skipped, returns the number of games added
+    pub fn add_games(&mut self, pgn: &str) -> usize{
+        let games: Vec<PgnGame> = pgn::read_games(pgn).into_iter().filter_map(Result::ok).collect();
+        for game in &games{
+            self.add_game(game);
+        }
+        games.len()
+    }
+
+    fn add_game(&mut self, game: &PgnGame){
+        let Ok(mut board) = game.start_position() else { return };
+        let mut line = vec![];
+        for mv in game.moves.iter().take(self.max_plies).map(|pgn_move| pgn_move.mv){
+            let key = board.polyglot_hash();
+            let white = board.is_white_move();
+            let stats = self.positions.entry(key).or_default();
+            let idx = match stats.iter().position(|(known, _)| known.long_algebraic_notation() == mv.long_algebraic_notation()) {
+                Some(idx) => idx,
+                None => {
+                    stats.push((mv, MoveStats::default()));
+                    stats.len() - 1
+                }
+            };
+            let stats = &mut stats[idx].1;
+            stats.games += 1;
+            match (game.result.as_str(), white) {
+                ("1-0", true) | ("0-1", false) => stats.wins += 1,
+                ("1-0", false) | ("0-1", true) => stats.losses += 1,
+                ("1/2-1/2", _) => stats.draws += 1,
+                _ => {}
+            }
+            line.push((key, mv));
+            board.make_move(mv);
+        }
+        let name = game.tag("Opening").or(game.tag("ECO")).unwrap_or(UNKNOWN_OPENING).to_string();
+        self.lines.push((name, line));
+    }
+
+    fn stats(&self, key: u64, mv: &Move) -> Option<&MoveStats>{
+        self.positions.get(&key)?.iter()
+            .find(|(known, _)| known.long_algebraic_notation() == mv.long_algebraic_notation())
+            .map(|(_, stats)| stats)
+    }
+
+    fn accepted(&self, stats: &MoveStats) -> bool{
+        stats.games >= self.min_games && stats.score() >= self.min_score
+    }
+
+    pub fn polyglot(&self) -> PolyglotBook{
+        let mut book = PolyglotBook::new();
+        for (key, moves) in &self.positions{
+            for (mv, stats) in moves.iter().filter(|(_, stats)| self.accepted(stats)){
+                book.add(*key, *mv, stats.weight());
+            }
+        }
+        book
+    }
+
+    // the format of the internal book, one line per distinct game prefix: name;weight;moves
+    pub fn text_book(&self) -> String{
+        let mut lines: HashMap<String, (String, u32)> = HashMap::new();
+        for (name, line) in &self.lines{
+            // cut the line at the first move that doesn't pass the filters
+            let moves: Vec<String> = line.iter()
+                .take_while(|(key, mv)| self.stats(*key, mv).is_some_and(|stats| self.accepted(stats)))
+                .map(|(_, mv)| mv.long_algebraic_notation())
+                .collect();
+            if moves.is_empty(){
+                continue;
+            }
+            lines.entry(moves.join(" ")).or_insert((name.clone(), 0)).1 += 1;
+        }
+        let mut lines: Vec<(String, (String, u32))> = lines.into_iter().collect();
+        lines.sort();
+        let mut res = String::from("# name;weight;moves\n");
+        for (moves, (name, weight)) in lines{
+            res.push_str(&format!("{};{};{}\n", name, weight, moves));
+        }
+        res
+    }
+}
+
+// makebook <games.pgn> <output> [--plies N] [--min-games N] [--min-score P], a .bin output is written as a polyglot book
+pub fn run(args: &[String]) -> Result<(), String>{
+    let usage = "usage: makebook <games.pgn> <output> [--plies N] [--min-games N] [--min-score P]";
+    let [input, output, flags @ ..] = args else { return Err(usage.into()) };
+    let mut builder = BookBuilder::new(DEFAULT_MAX_PLIES, 1, 0.0);
+    for flag in flags.chunks(2){
+        let value = flag.get(1).ok_or(usage)?;
+        let invalid = format!("invalid value for {}: {}", flag[0], value);
+        match flag[0].as_str() {
+            "--plies" => builder.max_plies = value.parse().map_err(|_| invalid)?,
+            "--min-games" => builder.min_games = value.parse().map_err(|_| invalid)?,
+            "--min-score" => builder.min_score = value.parse().map_err(|_| invalid)?,
+            _ => return Err(usage.into())
+        }
+    }
+    let pgn = fs::read_to_string(input).map_err(|err| format!("could not read {}: {}", input, err))?;
+    let games = builder.add_games(&pgn);
+    let written = if output.ends_with(".bin") {
+        let book = builder.polyglot();
+        book.save(output).map(|_| book.len())
+    }else {
+        let book = builder.text_book();
+        fs::write(output, &book).map(|_| book.lines().count() - 1)
+    };
+    let entries = written.map_err(|err| format!("could not write {}: {}", output, err))?;
+    println!("read {} games, wrote {} entries to {}", games, entries, output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::board::Board;
+
+    use super::BookBuilder;
+
+    const GAMES: &str = r#"
+[Event "test"]
+[Opening "Queens Gambit Declined"]
+[Result "1-0"]
+
+1. d4 d5 2. c4 {the gambit} e6 (2... c6 3. Nf3) 3. Nc3 $1 Nf6 1-0
+
+[Opening "Queens Gambit Declined"]
+[Result "1/2-1/2"]
+
+1.c4 e6 2.d4 d5 3.Nc3 Nf6 4.Bg5 1/2-1/2
+
+[Opening "Sicilian"]
+[Result "0-1"]
+
+1. e4 c5 ; comment to the end of the line
+2. Nf3 0-1
+"#;
+
+    #[test]
+    fn transpositions_share_statistics(){
+        let mut builder = BookBuilder::new(6, 1, 0.0);
+        assert_eq!(builder.add_games(GAMES), 3);
+
+        // both queens gambit games reach the position after 3. Nc3 by different move orders
+        let mut board = Board::default();
+        for mv in ["d4", "d5", "c4", "e6", "Nc3"]{
+            let mv = board.move_from_san(mv).unwrap();
+            board.make_move(mv);
+        }
+        let nf6 = board.move_from_san("Nf6").unwrap();
+        let stats = builder.stats(board.polyglot_hash(), &nf6).unwrap();
+        assert_eq!((stats.games, stats.wins, stats.draws, stats.losses), (2, 0, 1, 1));
+        assert_eq!(builder.polyglot().len(), 13);
+
+        // only moves played in both games are kept
+        builder.min_games = 2;
+        assert_eq!(builder.polyglot().len(), 2);
+        assert_eq!(builder.text_book(), "# name;weight;moves\n");
+
+        builder.min_games = 1;
+        builder.min_score = 50.0;
+        let book = builder.text_book();
+        assert!(book.contains("Queens Gambit Declined;1;d2d4\n"));
+        assert!(book.contains("Queens Gambit Declined;1;c2c4 e7e6 d2d4 d7d5 b1c3\n"));
+        assert!(!book.contains("Sicilian"));
+    }
+}
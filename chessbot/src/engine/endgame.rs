@@ -0,0 +1,127 @@
+use crate::{board::state::{PiceBoards, State}, constants};
+
+// evaluations are scaled by factor / SCALE_NORMAL
+pub const SCALE_NORMAL: i32 = 64;
+const SCALE_DRAW: i32 = 0;
+// no pawns left and the stronger side is at most a minor pice ahead, like KRvKR or KRBvKR
+const SCALE_PAWNLESS_SMALL_ADVANTAGE: i32 = 8;
+// only bishops of opposite colors and pawns, every extra pawn of the stronger side makes it more winnable
+const SCALE_OPPOSITE_BISHOPS: i32 = 16;
+const SCALE_OPPOSITE_BISHOPS_PER_PAWN: i32 = 8;
+// a rook each besides the bishops
+const SCALE_OPPOSITE_BISHOPS_WITH_ROOKS: i32 = 48;
+
+const MINOR_VALUE: u32 = 3;
+const ROOK_VALUE: u32 = 5;
+const QUEEN_VALUE: u32 = 9;
+
+// the material of one side, kings are not counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Material{
+    pawns: u32,
+    knights: u32,
+    light_bishops: u32,
+    dark_bishops: u32,
+    rooks: u32,
+    queens: u32
+}
+
+impl Material {
+    fn from_boards(boards: &PiceBoards) -> Material{
+        let bishops = boards.diagonal_sliders & !boards.orthoganal_sliders;
+        Material{
+            pawns: boards.pawns.count_ones(),
+            knights: boards.knights.count_ones(),
+            light_bishops: (bishops & constants::LIGHT_SQUARES).count_ones(),
+            dark_bishops: (bishops & !constants::LIGHT_SQUARES).count_ones(),
+            rooks: (boards.orthoganal_sliders & !boards.diagonal_sliders).count_ones(),
+            queens: (boards.orthoganal_sliders & boards.diagonal_sliders).count_ones()
+        }
+    }
+
+    fn bishops(&self) -> u32{
+        self.light_bishops + self.dark_bishops
+    }
+
+    fn minors(&self) -> u32{
+        self.knights + self.bishops()
+    }
+
+    fn heavies(&self) -> u32{
+        self.rooks + self.queens
+    }
+
+    fn value(&self) -> u32{
+        self.minors() * MINOR_VALUE + self.rooks * ROOK_VALUE + self.queens * QUEEN_VALUE
+    }
+}
+
+// scale factor for an evaluation from whites perspective, 0 means the position is a known draw
+pub fn scale_factor(state: &State, white_ahead: bool) -> i32{
+    let white = Material::from_boards(&state.white);
+    let black = Material::from_boards(&state.black);
+    let (strong, weak) = if white_ahead { (white, black) } else { (black, white) };
+
+    if strong.pawns == 0{
+        // a single minor pice can never force mate and two knights only with help from the opponent
+        if strong.heavies() == 0 && (strong.minors() <= 1 || (strong.knights == 2 && strong.bishops() == 0 && weak.pawns == 0)){
+            return SCALE_DRAW;
+        }
+        if strong.value() <= weak.value() + MINOR_VALUE{
+            return SCALE_PAWNLESS_SMALL_ADVANTAGE;
+        }
+    }
+
+    // bishops of opposite colors in an endgame, the defending king can usually blockade on the other color
+    let opposite_bishops = white.bishops() == 1 && black.bishops() == 1 && white.light_bishops != black.light_bishops;
+    if opposite_bishops && strong.knights + weak.knights + strong.queens + weak.queens == 0{
+        if strong.rooks + weak.rooks == 0{
+            let extra_pawns = strong.pawns.saturating_sub(weak.pawns) as i32;
+            return (SCALE_OPPOSITE_BISHOPS + extra_pawns * SCALE_OPPOSITE_BISHOPS_PER_PAWN).min(SCALE_NORMAL);
+        }
+        if strong.rooks == 1 && weak.rooks == 1{
+            return SCALE_OPPOSITE_BISHOPS_WITH_ROOKS;
+        }
+    }
+    SCALE_NORMAL
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::board::Board;
+
+    use super::{scale_factor, SCALE_NORMAL};
+
+    fn scale(fen: &str) -> i32{
+        scale_factor(&Board::from_fen(fen).state, true)
+    }
+
+    #[test]
+    fn known_draws(){
+        assert_eq!(scale("7k/8/8/8/8/8/8/KB6 w - - 0 1"), 0);
+        assert_eq!(scale("7k/8/8/8/8/8/8/KNN5 w - - 0 1"), 0);
+        assert_eq!(scale("7k/8/8/8/8/8/p7/KN6 w - - 0 1"), 0);
+        assert_eq!(scale("7k/8/8/8/8/8/8/KBN5 w - - 0 1"), SCALE_NORMAL);
+        assert_eq!(scale("7k/8/8/8/8/8/8/KR6 w - - 0 1"), SCALE_NORMAL);
+    }
+
+    #[test]
+    fn drawish_endgames(){
+        assert!(scale("r6k/8/8/8/8/8/8/KR6 w - - 0 1") < SCALE_NORMAL);
+        assert!(scale("r6k/8/8/8/8/8/8/KRB5 w - - 0 1") < SCALE_NORMAL);
+        assert_eq!(scale("r6k/8/8/8/8/8/8/KQ6 w - - 0 1"), SCALE_NORMAL);
+        assert!(scale("6bk/6pp/8/8/8/8/5PPP/K1B5 w - - 0 1") < SCALE_NORMAL);
+        assert_eq!(scale("7k/5bpp/8/8/8/8/5PPP/KB6 w - - 0 1"), SCALE_NORMAL);
+        // more extra pawns are easier to win even with opposite bishops
+        assert!(scale("6bk/6pp/8/8/8/8/4PPPP/K1B5 w - - 0 1") > scale("6bk/6pp/8/8/8/8/5PPP/K1B5 w - - 0 1"));
+        assert!(scale("r5bk/6pp/8/8/8/8/5PPP/KRB5 w - - 0 1") < SCALE_NORMAL);
+    }
+
+    #[test]
+    fn opposite_bishops_in_the_middlegame(){
+        // without the queens and rooks it would be scaled
+        assert!(scale("4k3/pp2bppp/8/8/8/8/PPP1BPPP/6K1 w - - 0 1") < SCALE_NORMAL);
+        assert_eq!(scale("r2qk2r/pp2bppp/2n2n2/2p1p3/4P3/2NP1N2/PPP1BPPP/R2QK2R w KQkq - 0 1"), SCALE_NORMAL);
+        assert_eq!(scale("3rk3/pp2bppp/8/8/8/8/PPP1BPPP/3RR1K1 w - - 0 1"), SCALE_NORMAL);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{board::{color::Color, state::{PiceBoards, State}, Board}, constants};
+use crate::{board::{color::Color, state::{PiceBoards, State}, Board}, constants, engine::endgame};
 
 // cant use i32::MIN cause if negetet it overflows
 pub const NEGATIVE_INF: i32 = i32::MIN + 10000;
@@ -19,7 +19,9 @@ fn evaluate_white(board: &Board) -> i32{
     eval += rooks_on_open_files(&board.state);
     eval += doubled_pawns(&board.state);
     // eval += king_endgame(&board.state, eval, _eg_phase);
-    eval
+
+    // pull the evaluation towards a draw in endgames that are hard or impossible to win
+    eval * endgame::scale_factor(&board.state, eval > 0) / endgame::SCALE_NORMAL
 }
 
 fn evaluate_pice_pos(board: &Board) -> (i32, i32, i32){
@@ -187,6 +189,14 @@ pub fn draw_by_repetition() -> i32{
     50
 }
 
+pub fn draw_by_fifty_move_rule() -> i32{
+    0
+}
+
+pub fn draw_by_insufficient_material() -> i32{
+    0
+}
+
 pub fn mate_ajusted_score(ply: usize) -> i32{
     NEGATIVE_INF + 10 + (ply as i32)
 }
@@ -230,6 +240,14 @@ mod tests {
         assert!(evaluate_turn(&board) > 100);
     }
 
+    #[test]
+    fn known_draw_is_zero(){
+        let board = Board::from_fen("7k/8/8/8/8/8/8/KB6 w - - 0 1");
+        assert_eq!(evaluate_white(&board), 0);
+        let board = Board::from_fen("7k/8/8/8/8/8/8/KNN5 b - - 0 1");
+        assert_eq!(evaluate_turn(&board), 0);
+    }
+
     #[test]
     fn test_eval(){
         let fen = "3RK3/5N1k/8/6p1/1P2q3/8/8/8 w - - 16 70";
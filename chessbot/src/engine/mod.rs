@@ -1,40 +1,53 @@
-use crate::{board::Board, engine::openingbook::Book, engine::searcher::Searcher, movegeneration::singlemove::Move, uci::uci_message::UciMessage};
+use crate::{board::{color::Color, Board}, engine::openingbook::Book, engine::options::OptionRegistry, engine::polyglot::{BookSelection, PolyglotBook}, engine::searcher::Searcher, engine::time_manager::{TimeManager, DEFAULT_SEARCH_TIME}, movegeneration::{perft, singlemove::Move}, uci::uci_message::UciMessage};
 use rand::prelude::*;
-use std::{sync::mpsc::{Receiver, RecvError, SendError, Sender}, time::Duration};
+use std::{sync::{atomic::{AtomicBool, Ordering}, mpsc::{Receiver, RecvError, SendError, Sender}, Arc}, thread::{self, JoinHandle}, time::Duration};
 
 mod evaluate;
+mod endgame;
 mod searcher;
 mod transposition_table;
+pub mod bookbuilder;
+pub mod testsuite;
 mod openingbook;
-
-
-
-const DEFAULT_SEARCH_TIME: Duration = Duration::from_millis(3000);
+mod options;
+mod polyglot;
+mod time_manager;
 
 pub struct UciEngine {
     board: Board,
-    searcher: Searcher,
-    #[allow(dead_code)]
-    settings: String,
+    // the searcher is moved to the search thread while a search is running
+    searcher: Option<Searcher>,
+    search_thread: Option<JoinHandle<Searcher>>,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    options: OptionRegistry,
     debug: bool,
     tx: Sender<UciMessage>,
     rx: Receiver<UciMessage>,
     book: Book,
-    in_book: bool
+    // loaded from the BookFile option, tried before the internal book
+    polyglot_book: Option<PolyglotBook>
 }
 
 impl UciEngine {
     
     pub fn new(tx: Sender<UciMessage>, rx: Receiver<UciMessage>) -> UciEngine{
+        let options = OptionRegistry::new();
+        let searcher = Searcher::new(options.spin(options::HASH) as usize, tx.clone());
+        let stop = searcher.stop_handle();
+        let ponder = searcher.ponder_handle();
         UciEngine { 
             board: Board::default(),
-            searcher: Searcher::new(5000000, tx.clone()), 
-            settings: "".into(), 
+            searcher: Some(searcher),
+            search_thread: None,
+            stop,
+            ponder,
+            options,
             debug: false,
             tx,
             rx,
             book: Book::new(),
-            in_book: false
+            polyglot_book: None
         }
     }
 
@@ -42,6 +55,7 @@ impl UciEngine {
         loop {
             let message = self.rx.recv()?;
             if let UciMessage::Quit = message{
+                self.stop_search();
                 self.tx.send(UciMessage::Quit).err(); // dont care about error thread is quitting
                 break;
             }
@@ -57,9 +71,9 @@ impl UciEngine {
         match message {
             UciMessage::Uci => {
                 self.tx.send(UciEngine::uci_id())?;
-                self.options().iter().for_each(|option| {
-                    self.tx.send(option.clone()).unwrap();
-                });
+                for option in self.options.announce(){
+                    self.tx.send(option)?;
+                }
                 self.tx.send(UciMessage::UciOk)?;
             },
             UciMessage::Debug(on) => {
@@ -68,76 +82,129 @@ impl UciEngine {
             UciMessage::IsReady => {
                 self.tx.send(UciMessage::ReadyOk)?;
             },
-            UciMessage::SetOption { .. } => {
-                //panic!("options not implemented")
-                
+            UciMessage::SetOption { name, value } => {
+                if let Err(err) = self.options.set(&name, &value).and_then(|option| self.apply_option(option)) {
+                    let mut info = UciMessage::new_empty_info();
+                    info.info_add_string(err);
+                    self.tx.send(info)?;
+                }
             },
             UciMessage::Register { .. } => {
-                todo!()
+                // the engine is free, there is nothing to register
             },
             UciMessage::UciNewGame => {
                 self.board = Board::default();
-                self.searcher.reset();
+                self.searcher().reset();
             },
             UciMessage::Position { fen, moves } => {
-                self.board = if let Some(fe) = fen { 
-                    self.in_book = false;
-                    Board::from_fen(&fe) 
-                } else { 
-                    self.in_book = true;
-                    self.book = Book::new();
-                    Board::default() 
+                self.stop_search();
+                let board = match fen {
+                    Some(fe) => Board::try_from_fen(&fe),
+                    None => Ok(Board::default())
+                };
+                // keep the old position rather than searching a broken one
+                self.board = match board {
+                    Ok(board) => board,
+                    Err(err) => {
+                        let mut info = UciMessage::new_empty_info();
+                        info.info_add_string(format!("invalid fen: {}", err));
+                        self.tx.send(info)?;
+                        return Ok(());
+                    }
                 };
                 for mv in moves{
                     self.board.make_move(mv);
-                    if self.in_book{
-                        self.book.play_move(&mv);
-                    }
-                    self.board.add_state_to_history();
                 }
             },
             UciMessage::Go { 
                     search_moves,
+                    ponder,
+                    wtime,
+                    btime,
+                    winc,
+                    binc,
+                    moves_to_go,
                     move_time,
                     depth,
-                    .. 
+                    nodes,
+                    mate,
+                    infinite,
                 } => {
-                self.searcher.set_search_moves(search_moves);
-                if let Some(millis) = move_time {
-                    self.searcher.duration = Duration::from_millis(millis);
-                }else {
-                    self.searcher.duration = DEFAULT_SEARCH_TIME;
-                }
-                let mut mv = Move::null_move();
-                if self.in_book{
-                    if let Some((book_move, name)) = self.book.get_random_move() {
-                        mv = book_move;
-                        //info
-                        {
+                // analysis and pondering always search, the bestmove can't be sent before stop or ponderhit
+                let use_book = self.options.check(options::OWN_BOOK) && !infinite && !ponder;
+                if use_book {
+                    if let Some(book) = &self.polyglot_book {
+                        let selection = match self.options.string(options::BOOK_SELECTION) {
+                            options::BOOK_BEST => BookSelection::Best,
+                            _ => BookSelection::Weighted
+                        };
+                        // the polyglot book is looked up by hash so it works from any position
+                        if let Some(book_move) = book.choose_move(&mut self.board, selection) {
                             let mut info = UciMessage::new_empty_info();
-                            info.info_add_string(format!("bookmove from opening {}", name));
+                            info.info_add_string(format!("bookmove from {}", self.options.string(options::BOOK_FILE)));
                             self.tx.send(info)?;
+                            self.tx.send(UciMessage::BestMove { best_move: book_move, ponder: None })?;
+                            return Ok(());
                         }
-                    }else {
-                        self.in_book = false;
+                    }
+                    // positions missing from the polyglot book can still be in the internal book,
+                    // it is also keyed by position so any fen or move order can be in book
+                    if let Some((book_move, name)) = self.book.get_random_move(&self.board) {
+                        let mut info = UciMessage::new_empty_info();
+                        info.info_add_string(format!("bookmove from opening {}", name));
+                        self.tx.send(info)?;
+                        self.tx.send(UciMessage::BestMove { best_move: book_move, ponder: None })?;
+                        return Ok(());
                     }
                 }
-                if mv.is_null_move(){
-                    mv = self.best_move(depth);
-                }
-                let res = UciMessage::BestMove { best_move: mv, ponder: None };
-                self.tx.send(res)?;
+
+                let (time_left, increment) = match self.board.get_turn() {
+                    Color::White => (wtime, winc),
+                    Color::Black => (btime, binc),
+                };
+                let time_manager = if infinite {
+                    TimeManager::infinite()
+                }else if let Some(millis) = move_time {
+                    TimeManager::fixed(Duration::from_millis(millis))
+                }else if let Some(millis) = time_left {
+                    TimeManager::from_clock(millis, increment.unwrap_or(0), moves_to_go)
+                }else if nodes.is_some() || mate.is_some() {
+                    // node and mate limited searches should not depend on the speed of the machine
+                    TimeManager::infinite()
+                }else {
+                    TimeManager::fixed(DEFAULT_SEARCH_TIME)
+                };
+                let multi_pv = self.options.spin(options::MULTI_PV) as usize;
+                let threads = self.options.spin(options::THREADS) as usize;
+                let searcher = self.searcher();
+                searcher.set_search_moves(search_moves);
+                searcher.set_multi_pv(multi_pv);
+                searcher.set_threads(threads);
+                searcher.set_node_limit(nodes);
+                searcher.set_mate_limit(mate);
+                searcher.set_time_manager(time_manager);
+                self.start_search(depth, infinite, ponder);
             },
             UciMessage::Ponderhit => {
-                panic!("not implemented")
+                // the search continues as a normal timed search
+                self.ponder.store(false, Ordering::Relaxed);
             },
             UciMessage::Stop => {
-                let mv = self.best_move(Some(1));
-                let res = UciMessage::BestMove { best_move: mv, ponder: None };
-                self.tx.send(res)?;
+                // the search thread sends the best move found so far when it exits
+                self.stop_search();
             },
             UciMessage::Quit => {
                 panic!("quit is not for the engine")
+            },
+            UciMessage::Perft { depth, hashed } => {
+                self.stop_search();
+                let result = perft::divide(&mut self.board.clone(), depth, hashed);
+                self.tx.send(UciMessage::Divide(result))?;
+            },
+            UciMessage::Unknown(err) => {
+                let mut info = UciMessage::new_empty_info();
+                info.info_add_string(err);
+                self.tx.send(info)?;
             }
             _ => {
                 
@@ -147,8 +214,81 @@ impl UciEngine {
         Ok(())
     }
 
-    fn options(&self) -> Vec<UciMessage>{
-        vec![] //no options currently implemented
+    // run iterative deepening on a separate thread so the engine can keep reading messages
+    fn start_search(&mut self, depth: Option<u8>, infinite: bool, ponder: bool){
+        let mut searcher = self.searcher.take().expect("no search is running");
+        let mut board = self.board.clone();
+        let tx = self.tx.clone();
+        let stop = self.stop.clone();
+        let pondering = self.ponder.clone();
+        self.stop.store(false, Ordering::Relaxed);
+        self.ponder.store(ponder, Ordering::Relaxed);
+        self.search_thread = Some(thread::spawn(move || {
+            let mv = UciEngine::best_move(&mut searcher, &mut board, depth);
+            // the search may finish early, but in analysis mode or while pondering the gui waits for stop or ponderhit
+            while !stop.load(Ordering::Relaxed) && (infinite || pondering.load(Ordering::Relaxed)) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            let res = UciMessage::BestMove { best_move: mv, ponder: searcher.ponder_move(mv) };
+            tx.send(res).err(); // the gui is gone if the send fails
+            searcher
+        }));
+    }
+
+    // abort the running search, if any, and take back the searcher
+    fn stop_search(&mut self){
+        if let Some(handle) = self.search_thread.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            self.searcher = Some(handle.join().expect("search thread panicked"));
+        }
+    }
+
+    fn searcher(&mut self) -> &mut Searcher{
+        self.stop_search();
+        self.searcher.as_mut().expect("searcher is returned when the search stops")
+    }
+
+    // options that only matter when a search starts are read from the registry at that time
+    fn apply_option(&mut self, option: &str) -> Result<(), String>{
+        match option {
+            options::HASH => {
+                let size = self.options.spin(options::HASH) as usize;
+                self.searcher().resize_hash(size);
+            },
+            options::CLEAR_HASH => {
+                self.searcher().reset();
+            },
+            // analysis of a position can be kept over restarts by saving the table to the hash file
+            options::SAVE_HASH | options::LOAD_HASH => {
+                let path = self.options.string(options::HASH_FILE).to_string();
+                if path.is_empty() {
+                    return Err(format!("set {} before saving or loading the hash", options::HASH_FILE));
+                }
+                let message = if option == options::SAVE_HASH {
+                    let entries = self.searcher().save_hash(&path).map_err(|err| format!("could not save hash to {}: {}", path, err))?;
+                    format!("saved {} hash entries to {}", entries, path)
+                }else {
+                    let entries = self.searcher().load_hash(&path).map_err(|err| format!("could not load hash from {}: {}", path, err))?;
+                    format!("loaded {} hash entries from {}", entries, path)
+                };
+                let mut info = UciMessage::new_empty_info();
+                info.info_add_string(message);
+                self.tx.send(info).err(); // the gui is gone if the send fails
+            },
+            options::BOOK_FILE => {
+                let path = self.options.string(options::BOOK_FILE);
+                self.polyglot_book = None;
+                if !path.is_empty() {
+                    let book = PolyglotBook::open(path).map_err(|err| format!("could not open book {}: {}", path, err))?;
+                    let mut info = UciMessage::new_empty_info();
+                    info.info_add_string(format!("loaded book {} with {} entries", path, book.len()));
+                    self.tx.send(info).err(); // the gui is gone if the send fails
+                    self.polyglot_book = Some(book);
+                }
+            },
+            _ => {}
+        }
+        Ok(())
     }
 
     fn uci_id() -> UciMessage{
@@ -163,11 +303,11 @@ impl UciEngine {
         "alfa-beta-bot"
     }
 
-    fn best_move(&mut self, depth: Option<u8>) -> Move{
-        let (mv, _score) = self.searcher.iterative_deepening(&mut self.board, depth);
+    fn best_move(searcher: &mut Searcher, board: &mut Board, depth: Option<u8>) -> Move{
+        let (mv, _score) = searcher.iterative_deepening(board, depth);
         
         if mv.is_null_move(){
-            let mut moves = self.board.get_possible_moves_turn();
+            let mut moves = board.get_possible_moves_turn();
             let mut rng = rand::thread_rng();
             moves.shuffle(&mut rng);
 
@@ -0,0 +1,115 @@
+use crate::uci::{uci_message::UciMessage, uci_option::{UciOption, UciOptionValue}};
+
+pub const HASH: &str = "Hash";
+pub const CLEAR_HASH: &str = "Clear Hash";
+pub const OWN_BOOK: &str = "OwnBook";
+pub const MULTI_PV: &str = "MultiPV";
+pub const PONDER: &str = "Ponder";
+pub const THREADS: &str = "Threads";
+pub const BOOK_FILE: &str = "BookFile";
+pub const BOOK_SELECTION: &str = "BookSelection";
+pub const HASH_FILE: &str = "HashFile";
+pub const SAVE_HASH: &str = "Save Hash";
+pub const LOAD_HASH: &str = "Load Hash";
+
+pub const BOOK_BEST: &str = "Best";
+pub const BOOK_WEIGHTED: &str = "Weighted";
+
+pub const DEFAULT_HASH_MB: i64 = 256;
+const MAX_HASH_MB: i64 = 16384;
+const MAX_MULTI_PV: i64 = 64;
+const MAX_THREADS: i64 = 256;
+
+// all options the engine announces on uci together with their current values
+pub struct OptionRegistry{
+    options: Vec<(UciOption, UciOptionValue)>
+}
+
+impl OptionRegistry {
+    pub fn new() -> OptionRegistry{
+        let options = vec![
+            UciOption::spin(HASH, DEFAULT_HASH_MB, 1, MAX_HASH_MB),
+            UciOption::button(CLEAR_HASH),
+            UciOption::check(OWN_BOOK, true),
+            UciOption::spin(MULTI_PV, 1, 1, MAX_MULTI_PV),
+            UciOption::check(PONDER, false),
+            UciOption::spin(THREADS, 1, 1, MAX_THREADS),
+            UciOption::string(BOOK_FILE, ""),
+            UciOption::combo(BOOK_SELECTION, BOOK_WEIGHTED, &[BOOK_BEST, BOOK_WEIGHTED]),
+            UciOption::string(HASH_FILE, ""),
+            UciOption::button(SAVE_HASH),
+            UciOption::button(LOAD_HASH),
+        ];
+        OptionRegistry {
+            options: options.into_iter().map(|option| {
+                let value = option.default_value();
+                (option, value)
+            }).collect()
+        }
+    }
+
+    pub fn announce(&self) -> Vec<UciMessage>{
+        self.options.iter().map(|(option, _)| UciMessage::Option(option.clone())).collect()
+    }
+
+    // validate and store a value from setoption, returns the name of the changed option
+    pub fn set(&mut self, name: &str, value: &str) -> Result<&'static str, String>{
+        let (option, current) = self.options.iter_mut()
+            .find(|(option, _)| option.is_named(name))
+            .ok_or(format!("no such option: {}", name.trim()))?;
+        *current = option.parse_value(value)?;
+        Ok(option.name)
+    }
+
+    pub fn spin(&self, name: &str) -> i64{
+        match self.value(name) {
+            UciOptionValue::Spin(v) => *v,
+            _ => panic!("{} is not a spin option", name)
+        }
+    }
+
+    pub fn check(&self, name: &str) -> bool{
+        match self.value(name) {
+            UciOptionValue::Check(v) => *v,
+            _ => panic!("{} is not a check option", name)
+        }
+    }
+
+    // string and combo options
+    pub fn string(&self, name: &str) -> &str{
+        match self.value(name) {
+            UciOptionValue::String(v) => v,
+            _ => panic!("{} is not a string option", name)
+        }
+    }
+
+    fn value(&self, name: &str) -> &UciOptionValue{
+        self.options.iter()
+            .find(|(option, _)| option.is_named(name))
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| panic!("{} is not a registered option", name))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{OptionRegistry, BOOK_BEST, BOOK_FILE, BOOK_SELECTION, DEFAULT_HASH_MB, HASH, OWN_BOOK};
+
+    #[test]
+    fn set_and_read_options(){
+        let mut options = OptionRegistry::new();
+        assert_eq!(options.spin(HASH), DEFAULT_HASH_MB);
+        assert_eq!(options.set("hash", "64"), Ok(HASH));
+        assert_eq!(options.spin(HASH), 64);
+        assert_eq!(options.set("OwnBook", "false"), Ok(OWN_BOOK));
+        assert!(!options.check(OWN_BOOK));
+        assert!(options.set("Hash", "-3").is_err());
+        assert_eq!(options.spin(HASH), 64);
+        assert!(options.set("Contempt", "10").is_err());
+        assert_eq!(options.set("BookFile", "books/performance.bin"), Ok(BOOK_FILE));
+        assert_eq!(options.string(BOOK_FILE), "books/performance.bin");
+        assert_eq!(options.set("BookSelection", "best"), Ok(BOOK_SELECTION));
+        assert_eq!(options.string(BOOK_SELECTION), BOOK_BEST);
+        assert!(options.set("BookSelection", "Worst").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+use std::{fs, io, path::Path};
+
+use rand::Rng;
+
+use crate::{board::Board, movegeneration::singlemove::{Move, MoveType}};
+
+// http://hgm.nubati.net/book_format.html
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSelection {
+    // always play the move with the highest weight
+    Best,
+    // pick a move at random with a probability proportional to its weight
+    Weighted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PolyglotEntry{
+    key: u64,
+    mv: u16,
+    weight: u16
+}
+
+// an opening book in the polyglot format, the entries are sorted by the polyglot hash of the position
+pub struct PolyglotBook{
+    entries: Vec<PolyglotEntry>
+}
+
+impl PolyglotBook {
+    pub fn new() -> PolyglotBook{
+        PolyglotBook { entries: vec![] }
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<PolyglotBook>{
+        PolyglotBook::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<PolyglotBook>{
+        if !bytes.len().is_multiple_of(ENTRY_SIZE){
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "book size is not a multiple of 16 bytes"));
+        }
+        let mut entries: Vec<PolyglotEntry> = bytes.chunks_exact(ENTRY_SIZE).map(|chunk| {
+            PolyglotEntry{
+                key: u64::from_be_bytes(chunk[0..8].try_into().unwrap()),
+                mv: u16::from_be_bytes(chunk[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(chunk[10..12].try_into().unwrap())
+                // the last 4 bytes are learning data which isn't used
+            }
+        }).collect();
+        // books should already be sorted but the lookup depends on it
+        entries.sort_by_key(|entry| entry.key);
+        Ok(PolyglotBook { entries })
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()>{
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8>{
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|entry| (entry.key, u16::MAX - entry.weight));
+        entries.iter().flat_map(|entry| {
+            let mut bytes = Vec::with_capacity(ENTRY_SIZE);
+            bytes.extend(entry.key.to_be_bytes());
+            bytes.extend(entry.mv.to_be_bytes());
+            bytes.extend(entry.weight.to_be_bytes());
+            bytes.extend(0u32.to_be_bytes());
+            bytes
+        }).collect()
+    }
+
+    // add a move for the position with the given polyglot hash
+    pub fn add(&mut self, key: u64, mv: Move, weight: u16){
+        self.entries.push(PolyglotEntry { key, mv: PolyglotBook::encode_move(mv), weight });
+    }
+
+    pub fn len(&self) -> usize{
+        self.entries.len()
+    }
+
+    fn lookup(&self, key: u64) -> &[PolyglotEntry]{
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+        &self.entries[start..end]
+    }
+
+    // the book move for the current position, positions are found by hash so transpositions are handled
+    pub fn choose_move(&self, board: &mut Board, selection: BookSelection) -> Option<Move>{
+        let candidates: Vec<(Move, u16)> = self.lookup(board.polyglot_hash()).iter()
+            .filter_map(|entry| PolyglotBook::decode_move(board, entry.mv).map(|mv| (mv, entry.weight)))
+            .collect();
+        match selection {
+            BookSelection::Best => candidates.iter().max_by_key(|(_, weight)| *weight).map(|(mv, _)| *mv),
+            BookSelection::Weighted => {
+                let total: u32 = candidates.iter().map(|(_, weight)| *weight as u32).sum();
+                if total == 0{
+                    return candidates.first().map(|(mv, _)| *mv);
+                }
+                let mut pick = rand::thread_rng().gen_range(0..total);
+                for (mv, weight) in candidates{
+                    if pick < weight as u32{
+                        return Some(mv);
+                    }
+                    pick -= weight as u32;
+                }
+                None
+            }
+        }
+    }
+
+    fn encode_move(mv: Move) -> u16{
+        let from = mv.from() as u16;
+        let to = match (mv.move_type(), mv.to()) {
+            (MoveType::Castle, 6) => 7,
+            (MoveType::Castle, 2) => 0,
+            (MoveType::Castle, 62) => 63,
+            (MoveType::Castle, 58) => 56,
+            (_, to) => to
+        } as u16;
+        let promotion = match mv.move_type() {
+            MoveType::PromotionKnight => 1,
+            MoveType::PromotionBishop => 2,
+            MoveType::PromotionRook => 3,
+            MoveType::PromotionQueen => 4,
+            _ => 0
+        };
+        promotion << 12 | from << 6 | to
+    }
+
+    // to file 0-2, to row 3-5, from file 6-8, from row 9-11 and promotion 12-14, castling is encoded as the king capturing its rook
+    fn decode_move(board: &mut Board, mv: u16) -> Option<Move>{
+        let mut to = (mv & 0x3F) as u8;
+        let from = ((mv >> 6) & 0x3F) as u8;
+        let promotion = match (mv >> 12) & 0b111 {
+            1 => Some(MoveType::PromotionKnight),
+            2 => Some(MoveType::PromotionBishop),
+            3 => Some(MoveType::PromotionRook),
+            4 => Some(MoveType::PromotionQueen),
+            _ => None
+        };
+        let moves = board.get_possible_moves_turn();
+        if moves.iter().any(|legal| legal.from() == from && legal.move_type() == MoveType::Castle){
+            to = match (from, to) {
+                (4, 7) => 6,
+                (4, 0) => 2,
+                (60, 63) => 62,
+                (60, 56) => 58,
+                _ => to
+            };
+        }
+        moves.into_iter().find(|legal| {
+            legal.from() == from && legal.to() == to && match promotion {
+                Some(typ) => legal.move_type() == typ,
+                None => !legal.move_type().is_promotion()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::board::Board;
+
+    use super::{BookSelection, PolyglotBook};
+
+    fn entry(key: u64, mv: u16, weight: u16) -> Vec<u8>{
+        let mut bytes = vec![];
+        bytes.extend(key.to_be_bytes());
+        bytes.extend(mv.to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+        bytes
+    }
+
+    // polyglot move encoding of from and to squares
+    fn encode(from: u16, to: u16) -> u16{
+        from << 6 | to
+    }
+
+    #[test]
+    fn read_and_choose_moves(){
+        let start = Board::default().polyglot_hash();
+        let mut bytes = entry(start, encode(12, 28), 10);
+        bytes.extend(entry(start, encode(11, 27), 30));
+        bytes.extend(entry(start + 1, encode(6, 21), 50));
+        let book = PolyglotBook::from_bytes(&bytes).unwrap();
+        assert_eq!(book.len(), 3);
+
+        let mut board = Board::default();
+        let best = book.choose_move(&mut board, BookSelection::Best).unwrap();
+        assert_eq!(best.long_algebraic_notation(), "d2d4");
+        for _ in 0..20{
+            let mv = book.choose_move(&mut board, BookSelection::Weighted).unwrap().long_algebraic_notation();
+            assert!(mv == "d2d4" || mv == "e2e4");
+        }
+
+        let mut board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+        assert!(book.choose_move(&mut board, BookSelection::Best).is_none());
+        assert!(PolyglotBook::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn write_and_read_back(){
+        let mut board = Board::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let key = board.polyglot_hash();
+        let mut book = PolyglotBook::new();
+        for mv in ["e1c1", "b7a8n", "e1g1"]{
+            let mv = board.move_from_long_algebraic_notation(mv.into()).unwrap();
+            book.add(key, mv, 1);
+        }
+        let book = PolyglotBook::from_bytes(&book.to_bytes()).unwrap();
+        let mut moves: Vec<String> = book.lookup(key).iter()
+            .map(|entry| PolyglotBook::decode_move(&mut board, entry.mv).unwrap().long_algebraic_notation())
+            .collect();
+        moves.sort();
+        assert_eq!(moves, vec!["b7a8n", "e1c1", "e1g1"]);
+    }
+
+    #[test]
+    fn castling_is_king_takes_rook(){
+        let fen = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1";
+        let key = Board::from_fen(fen).polyglot_hash();
+        let book = PolyglotBook::from_bytes(&entry(key, encode(4, 7), 1)).unwrap();
+        let mut board = Board::from_fen(fen);
+        assert_eq!(book.choose_move(&mut board, BookSelection::Best).unwrap().long_algebraic_notation(), "e1g1");
+    }
+}
@@ -1,40 +1,140 @@
-use crate::{board::Board, engine::{evaluate::{self, NEGATIVE_INF, POSETIVE_INF}, transposition_table::{TranspositionsFlag, TranspositionsTable}}, movegeneration::{moveorder::{self, MoveOrder}, singlemove::Move}, uci::uci_message::UciMessage};
-use rand::prelude::*;
-use std::{collections::HashSet, time::{Duration, Instant}};
+use crate::{board::Board, engine::{evaluate::{self, NEGATIVE_INF, POSETIVE_INF}, time_manager::{TimeManager, DEFAULT_SEARCH_TIME}, transposition_table::{TranspositionsFlag, TranspositionsTable}}, movegeneration::{moveorder::{MoveOrder, ReductionContext}, see, singlemove::{Move, MoveType}}, uci::uci_message::UciMessage};
+use std::{io, sync::{atomic::{AtomicBool, Ordering}, mpsc, Arc}, thread};
 use std::sync::mpsc::Sender;
 
 
 const MAX_EXTENTIONS: usize = 6;
 const NULL_MOVE_REDUCTION: usize = 2;
 const WINDOW: i32 = 50;
-const LMR: usize = 1;
+
+// triangular pv table, the line at a ply is the best move found there followed by the line of the ply below
+struct PvTable{
+    lines: Vec<Vec<Move>>
+}
+
+impl PvTable {
+    fn new() -> PvTable{
+        PvTable { lines: vec![] }
+    }
+
+    // called when a node is entered, nodes that return early have no line
+    fn clear(&mut self, ply: usize){
+        while self.lines.len() <= ply + 1 {
+            self.lines.push(vec![]);
+        }
+        self.lines[ply].clear();
+    }
+
+    fn update(&mut self, ply: usize, mv: Move){
+        let (head, tail) = self.lines.split_at_mut(ply + 1);
+        head[ply].clear();
+        head[ply].push(mv);
+        head[ply].extend_from_slice(&tail[0]);
+    }
+
+    fn root_line(&self) -> Vec<Move>{
+        self.lines.first().cloned().unwrap_or_default()
+    }
+}
 
 pub struct Searcher{
-    traspos_table: TranspositionsTable,
+    // shared with the helper threads of a lazy smp search
+    traspos_table: Arc<TranspositionsTable>,
+    // nodes of the current search, the helper threads are added when it is done
     pub searches: u64,
-    start_time: Instant,
-    pub duration: Duration,
+    time_manager: TimeManager,
+    stop: Arc<AtomicBool>,
+    ponder: Arc<AtomicBool>,
+    pondering: bool,
+    best_line: Vec<Move>,
+    pv_table: PvTable,
+    // static evaluation of the nodes on the current line by ply, None when in check
+    static_evals: Vec<Option<i32>>,
     tx: Sender<UciMessage>,
     search_moves: Option<Vec<String>>,
+    multi_pv: usize,
+    excluded_root_moves: Vec<Move>,
+    node_limit: Option<u64>,
+    mate_limit: Option<u8>,
     move_order: MoveOrder,
-    history: HashSet<u64>
+    threads: usize,
+    thread_id: usize
 }
 
 impl Searcher {
-    pub fn new(traspos_table_size: usize, tx: Sender<UciMessage>) -> Searcher{
+    pub fn new(hash_size_mb: usize, tx: Sender<UciMessage>) -> Searcher{
         Searcher{
-            traspos_table: TranspositionsTable::new(traspos_table_size),
+            traspos_table: Arc::new(TranspositionsTable::with_size_mb(hash_size_mb)),
             searches: 0,
-            start_time: Instant::now(),
-            duration: Duration::from_millis(3000),
+            time_manager: TimeManager::fixed(DEFAULT_SEARCH_TIME),
+            stop: Arc::new(AtomicBool::new(false)),
+            ponder: Arc::new(AtomicBool::new(false)),
+            pondering: false,
+            best_line: vec![],
+            pv_table: PvTable::new(),
+            static_evals: vec![],
             tx,
             search_moves: None,
+            multi_pv: 1,
+            excluded_root_moves: vec![],
+            node_limit: None,
+            mate_limit: None,
             move_order: MoveOrder::default(),
-            history: HashSet::new()
+            threads: 1,
+            thread_id: 0
         }
     }
 
+    // lazy smp, the helper threads search the same position and only communicate through the shared table.
+    // the result of the main thread is always the one that is played.
+    // node limited searches use one thread, the helpers would make the result depend on the scheduling
     pub fn iterative_deepening(&mut self, board: &mut Board, max_depth: Option<u8>) -> (Move, i32){
+        self.traspos_table.new_search();
+        if self.threads <= 1 || self.node_limit.is_some(){
+            return self.search_iterations(board, max_depth);
+        }
+        let helpers_stop = Arc::new(AtomicBool::new(false));
+        let mut helpers: Vec<(Searcher, Board)> = (1..self.threads)
+            .map(|id| (self.helper(id, helpers_stop.clone()), board.clone()))
+            .collect();
+        let res = thread::scope(|scope| {
+            for (helper, helper_board) in helpers.iter_mut(){
+                scope.spawn(move || helper.search_iterations(helper_board, None));
+            }
+            let res = self.search_iterations(board, max_depth);
+            helpers_stop.store(true, Ordering::Relaxed);
+            res
+        });
+        self.searches += helpers.iter().map(|(helper, _)| helper.searches).sum::<u64>();
+        res
+    }
+
+    // a searcher sharing the table but with its own killers and history, its info messages are discarded
+    fn helper(&self, thread_id: usize, stop: Arc<AtomicBool>) -> Searcher{
+        let (tx, _) = mpsc::channel();
+        Searcher{
+            traspos_table: self.traspos_table.clone(),
+            searches: 0,
+            time_manager: TimeManager::infinite(),
+            stop,
+            ponder: Arc::new(AtomicBool::new(false)),
+            pondering: false,
+            best_line: vec![],
+            pv_table: PvTable::new(),
+            static_evals: vec![],
+            tx,
+            search_moves: self.search_moves.clone(),
+            multi_pv: 1,
+            excluded_root_moves: vec![],
+            node_limit: None,
+            mate_limit: self.mate_limit,
+            move_order: MoveOrder::default(),
+            threads: 1,
+            thread_id
+        }
+    }
+
+    fn search_iterations(&mut self, board: &mut Board, max_depth: Option<u8>) -> (Move, i32){
         // dont know if table needs clearing
         // self.traspos_table.clear();
         self.move_order.clear();
@@ -44,13 +144,20 @@ impl Searcher {
         let mut eval = 0;
         let mut alpha = NEGATIVE_INF;
         let mut beta = POSETIVE_INF;
-        let mut depth = 1;
+        // every other helper starts one ply deeper so the threads dont search the same depths in lockstep
+        let mut depth = 1 + self.thread_id % 2;
+        let mut iteration_start = 0;
+        self.best_line.clear();
+        self.excluded_root_moves.clear();
+        let root_moves = board.get_possible_moves_turn().iter().filter(|mv| self.root_move_allowed(mv)).count();
+        let multi_pv = self.multi_pv.min(root_moves).max(1);
         
-        self.start_time = Instant::now();
+        self.time_manager.start();
+        self.pondering = self.ponder.load(Ordering::Relaxed);
         loop {
             let (mv, val_depth) = self.search_alpha_beta(board, alpha, beta, depth, 0, 0, true);
-            // exit if searchtime has elapsed
-            if self.start_time.elapsed() >= self.duration {
+            // exit if searchtime has elapsed or the gui asked to stop
+            if self.should_stop() {
                 if !mv.is_null_move(){
                     best_move = mv;
                 }
@@ -62,8 +169,8 @@ impl Searcher {
             if (val_depth <= alpha) || (val_depth >= beta) {
                 // send info about faild window
                 {
-                    info.info_add_string(format!("window search failed, nodes waisted {}, val: {}, alpha: {}, beta: {}", self.searches, val_depth, alpha, beta));
-                    self.searches = 0;
+                    info.info_add_string(format!("window search failed, nodes waisted {}, val: {}, alpha: {}, beta: {}", self.searches - iteration_start, val_depth, alpha, beta));
+                    iteration_start = self.searches;
                     self.tx.send(info).err();
                     info = UciMessage::new_empty_info();
                 }
@@ -85,22 +192,30 @@ impl Searcher {
             
             // send info
             {
-                info.info_add_depth(depth as u8);
-                info.info_add_nodes(self.searches);
-                self.searches = 0;
-                if evaluate::is_mate_score(eval){
-                    info.info_add_score_mate(evaluate::to_mate(eval));
-                }else {
-                    info.info_add_score_cp(eval);
-                }
-                info.info_add_hashfull(self.traspos_table.get_permill_fill());
-                let best_line = self.get_current_best_line(board);
-                if !best_line.is_empty(){
-                    info.info_add_pv(best_line);
+                let best_line = self.pv_table.root_line();
+                let multipv_index = if multi_pv > 1 { Some(1) } else { None };
+                self.send_line_info(depth, multipv_index, eval, best_line.clone());
+                self.best_line = best_line;
+            }
+
+            // search the next best moves at the same depth by excluding the ones already found
+            if multi_pv > 1 {
+                self.excluded_root_moves.push(best_move);
+                for idx in 2..=multi_pv{
+                    let (line_move, line_eval) = self.search_alpha_beta(board, NEGATIVE_INF, POSETIVE_INF, depth, 0, 0, true);
+                    if self.should_stop() || line_move.is_null_move(){
+                        break;
+                    }
+                    let line = self.pv_table.root_line();
+                    self.send_line_info(depth, Some(idx as u16), line_eval, line);
+                    self.excluded_root_moves.push(line_move);
                 }
-                self.tx.send(info).err();
-                info = UciMessage::new_empty_info();
+                self.excluded_root_moves.clear();
+                // the root entry now holds the last line, put the best move back so the next iteration starts with it
+                let zobrist = board.get_zobrist_hash();
+                self.traspos_table.record_entry(zobrist, depth, 0, eval, TranspositionsFlag::Exact, Some(best_move));
             }
+            iteration_start = self.searches;
 
             // early exit if mate found
             if evaluate::is_mate_score(val_depth){ 
@@ -111,6 +226,19 @@ impl Searcher {
                     break;
                 }
             }
+            // a mate within the limit would have been found by now
+            if let Some(moves) = self.mate_limit {
+                if depth >= 2 * (moves.max(1) as usize){
+                    break;
+                }
+            }
+
+            // stop early if the best move is stable, search longer if the score dropped
+            self.time_manager.update_iteration(best_move, eval);
+            self.update_ponder();
+            if !self.pondering && self.time_manager.soft_limit_reached(){
+                break;
+            }
             depth += 1;
             if depth > 200{ break; }
         }
@@ -122,20 +250,28 @@ impl Searcher {
         let mut flag = TranspositionsFlag::UpperBound;
         let zobrist = board.get_zobrist_hash();
         self.searches += 1;
+        self.pv_table.clear(ply);
+        // nodes searched with an open window, the others only have to prove a bound
+        let pv_node = alpha.saturating_add(1) < beta;
 
         // draw by repetition
-        if ply != 0 && board.game_history_contains(zobrist){
+        if ply != 0 && board.is_repetition(ply){
             return (Move::null_move(), evaluate::draw_by_repetition());
         }
 
-        if !self.history.insert(zobrist){
-            return (Move::null_move(), evaluate::draw_by_repetition());
+        // draw by the fifty move rule, 100 plies without a capture or pawn move
+        if ply != 0 && board.is_fifty_move_draw(){
+            return (Move::null_move(), evaluate::draw_by_fifty_move_rule());
         }
 
-        // lookup the position if it exists in the table
-        if ply != 0{
+        // no sequence of legal moves can lead to mate
+        if ply != 0 && board.is_insufficient_material(){
+            return (Move::null_move(), evaluate::draw_by_insufficient_material());
+        }
+
+        // lookup the position if it exists in the table, pv nodes are searched so the line stays complete
+        if ply != 0 && !pv_node{
             if let Some(val) = self.traspos_table.lookup_eval(zobrist, depth, ply, alpha, beta){
-                self.history.remove(&zobrist);
                 if let Some(best) = self.traspos_table.get_best_move(zobrist) {
                     return (best, val);
                 }
@@ -147,7 +283,6 @@ impl Searcher {
         // full depth is reached return nullmove and evaluation
         if depth == 0{
             let val = self.search_stable_pos(board, alpha, beta);
-            self.history.remove(&zobrist);
             return (Move::null_move(),val);
         }
 
@@ -157,18 +292,21 @@ impl Searcher {
         let mut moves = board.get_possible_moves_turn();
         let check = board.in_check();    
         let extend = if check && extentions < MAX_EXTENTIONS { 1 } else { 0 };
+        let improving = self.improving(board, ply, check);
         
+        // the first move is searched with the full window, the rest only have to show they are no better
+        let mut searched_first = false;
+
         // start with the previous best move in the position
-        if let Some(mv) = self.traspos_table.get_best_move(zobrist) {
+        if let Some(mv) = self.traspos_table.get_best_move(zobrist).filter(|mv| ply != 0 || self.root_move_allowed(mv)) {
             board.make_move(mv);
             let (_,mut val) = self.search_alpha_beta(board, -beta, -alpha, depth - 1 + extend, ply + 1, extentions + extend, false);
             val = -val;
             board.undo_last_move();
 
             // return if searchtime has elapsed
-            if self.start_time.elapsed() >= self.duration {
+            if self.should_stop() {
                 // println!("PV-time, bm: {}, mv: {}, eval: {}, ply: {}, depth: {}", best_move.long_algebraic_notation(), mv.long_algebraic_notation(), alpha, ply, depth);
-                self.history.remove(&zobrist);
                 return (mv, alpha);
             }
 
@@ -176,7 +314,6 @@ impl Searcher {
             if val >= beta{
                 self.traspos_table.record_entry(zobrist, depth, ply, val, TranspositionsFlag::LowerBound, Some(mv));
                 self.move_order.add_killer(&mv, ply);
-                self.history.remove(&zobrist);
                 return (mv, beta);
             }
 
@@ -185,14 +322,14 @@ impl Searcher {
                 flag = TranspositionsFlag::Exact;
                 best_move = mv;
                 alpha = val;
-                self.move_order.add_history(&mv, depth, board.is_white_move())
+                self.move_order.add_history(&mv, depth, board.is_white_move());
+                self.pv_table.update(ply, mv);
             }
-            
+            searched_first = true;
         }
 
         // return 0 if stalemate else -Inf checkmate
         if moves.is_empty(){
-            self.history.remove(&zobrist);
             if board.in_check(){
                 return (Move::null_move(), evaluate::mate_ajusted_score(ply));
             }else {
@@ -205,13 +342,9 @@ impl Searcher {
             moves.retain(|mv| mv.from_to_mask() != bm.from_to_mask());
         }
 
-        // check if specified seachmoves from gui
+        // check if specified seachmoves from gui or moves already reported as a better multipv line
         if ply == 0{
-            if let Some(search_moves) = &self.search_moves{
-                moves.retain(|mv| {
-                    search_moves.contains(&mv.long_algebraic_notation())
-                });
-            }
+            moves.retain(|mv| self.root_move_allowed(mv));
         }
 
         //random ordering for moves before ordering is implemented
@@ -220,40 +353,60 @@ impl Searcher {
 
 
         // nullmove reduction
-        if !prev_nullmove && depth >= 3 && !check && (board.state.white.bitmap_all() | board.state.black.bitmap_all()).count_ones() > 10 && best_move.is_null_move(){
+        if self.mate_limit.is_none() && !pv_node && !prev_nullmove && depth >= 3 && !check && (board.state.white.bitmap_all() | board.state.black.bitmap_all()).count_ones() > 10 && best_move.is_null_move(){
             board.make_null_move();
             let (_, mut val) = self.search_alpha_beta(board, -beta, -beta + 1, depth - 1 - NULL_MOVE_REDUCTION, ply + 1, extentions, true);
             val = -val;
             board.undo_null_move();
             if val >= beta{
-                self.history.remove(&zobrist);
                 return (Move::null_move(),val);
             }
         }
         self.move_order.sort_moves(&mut moves, &board, ply);
         // (&mut moves, board);
-        let mut idx = 0;
-        for mv in moves{
+        // idx counts the moves searched before, including the previous best move
+        for (idx, mv) in (usize::from(searched_first)..).zip(moves){
+            let white = board.is_white_move();
+            let capture = board.get_pice_pos(mv.to()).is_some() || mv.move_type() == MoveType::Pessant;
             board.make_move(mv);
-            let mut val = 0;
-            let mut full = true;
-            if moveorder::late_move_reduction(depth, extend, check, &mv, idx){
-                (_, val) = self.search_alpha_beta(board, -beta, -alpha, depth - 1 - LMR, ply + 1, extentions + extend, false);
-                if -val <= alpha{
-                    full = false;
+            let new_depth = depth - 1 + extend;
+            let mut val;
+            if !searched_first {
+                (_, val) = self.search_alpha_beta(board, -beta, -alpha, new_depth, ply + 1, extentions + extend, false);
+                val = -val;
+                searched_first = true;
+            }else {
+                // zero window, reduced for late moves
+                let reduction = if self.mate_limit.is_none() {
+                    self.move_order.late_move_reduction(&mv, ReductionContext {
+                        depth,
+                        move_number: idx,
+                        ply,
+                        white,
+                        pv_node,
+                        capture,
+                        in_check: check,
+                        gives_check: board.in_check(),
+                        improving
+                    })
+                }else { 0 };
+                (_, val) = self.search_alpha_beta(board, -alpha - 1, -alpha, new_depth - reduction, ply + 1, extentions + extend, false);
+                val = -val;
+                if val > alpha && reduction > 0 {
+                    (_, val) = self.search_alpha_beta(board, -alpha - 1, -alpha, new_depth, ply + 1, extentions + extend, false);
+                    val = -val;
+                }
+                // the move may be the new best, search it again with the full window to get its score and line
+                if val > alpha && val < beta {
+                    (_, val) = self.search_alpha_beta(board, -beta, -alpha, new_depth, ply + 1, extentions + extend, false);
+                    val = -val;
                 }
             }
-            if full{
-                (_, val) = self.search_alpha_beta(board, -beta, -alpha, depth - 1 + extend, ply + 1, extentions + extend, false);
-
-            }
-            val = -val;
             board.undo_last_move();
 
             // return if searchtime has elapsed
-            if self.start_time.elapsed() >= self.duration {
+            if self.should_stop() {
                 // println!("time, bm: {}, eval: {}, ply: {}, depth: {}", best_move.long_algebraic_notation(), alpha, ply, depth);
-                self.history.remove(&zobrist);
                 return (best_move, alpha);
             }
 
@@ -261,7 +414,6 @@ impl Searcher {
             if val >= beta{
                 self.traspos_table.record_entry(zobrist, depth, ply, val, TranspositionsFlag::LowerBound, Some(mv));
                 self.move_order.add_killer(&mv, ply);
-                self.history.remove(&zobrist);
                 return (mv, beta);
             }
 
@@ -270,16 +422,33 @@ impl Searcher {
                 flag = TranspositionsFlag::Exact;
                 alpha = val;
                 best_move = mv;
-                self.move_order.add_history(&mv, depth, board.is_white_move())
+                self.move_order.add_history(&mv, depth, board.is_white_move());
+                self.pv_table.update(ply, mv);
             }
-            idx += 1; 
         }      
         //  record the position and the best move found
         self.traspos_table.record_entry(zobrist, depth, ply, alpha, flag, Some(best_move));
-        self.history.remove(&zobrist);
         (best_move, alpha)
     }
 
+    // remembers the static evaluation of the node, it is improving when better than at the last node of the same side.
+    // after a position in check the one before that is compared
+    fn improving(&mut self, board: &Board, ply: usize, check: bool) -> bool{
+        if self.static_evals.len() <= ply {
+            self.static_evals.resize(ply + 1, None);
+        }
+        let eval = if check { None } else { Some(evaluate::evaluate_turn(board)) };
+        self.static_evals[ply] = eval;
+        let before = [2, 4].into_iter()
+            .filter(|back| *back <= ply)
+            .find_map(|back| self.static_evals[ply - back]);
+        match (eval, before) {
+            (Some(eval), Some(before)) => eval > before,
+            (Some(_), None) => true,
+            _ => false
+        }
+    }
+
     fn search_stable_pos(&mut self, board: &mut Board, mut alpha: i32, beta: i32) ->  i32{
         self.searches += 1;
             
@@ -293,14 +462,14 @@ impl Searcher {
         //get captures only
         let mut moves = board.get_possible_captures_turn();
 
-        //random ordering for moves before ordering is implemented
-        let mut rng = rand::thread_rng();
-        moves.shuffle(&mut rng);
-
         self.move_order.sort_moves(&mut moves, &board, 100);
         // evaluate::sort_moves(&mut moves, board);
 
         for mv in moves{
+            // a capture that loses material can't raise alpha above the stand pat score
+            if see::see(board, &mv) < 0 {
+                continue;
+            }
             board.make_move(mv);
             val = -self.search_stable_pos(board, -beta, -alpha);
             board.undo_last_move();
@@ -314,44 +483,128 @@ impl Searcher {
             alpha = alpha.max(val);
 
             // return if searchtime has elapsed
-            if self.start_time.elapsed() >= self.duration {
+            if self.should_stop() {
                 break;
             }
         }       
         alpha
     }
 
-    fn get_current_best_line(&mut self, board: &mut Board) -> Vec<Move>{
-        let mut res = vec![];
-        let mut been: HashSet<u64> = HashSet::new();
-        let mut zob = board.get_zobrist_hash();
-        while let Some(mv) = self.traspos_table.get_best_move(zob) {
-            been.insert(zob);
-            res.push(mv);
-            board.make_move(mv);
-            zob = board.get_zobrist_hash();
-            if been.contains(&zob){ break; }
+    fn root_move_allowed(&self, mv: &Move) -> bool{
+        if let Some(search_moves) = &self.search_moves{
+            if !search_moves.contains(&mv.long_algebraic_notation()){
+                return false;
+            }
         }
-        res.iter().for_each(|_| {
-            board.undo_last_move();
-        });
-        res
+        !self.excluded_root_moves.iter().any(|excluded| excluded.get_hash() == mv.get_hash())
+    }
+
+    fn send_line_info(&mut self, depth: usize, multipv: Option<u16>, eval: i32, line: Vec<Move>){
+        let mut info = UciMessage::new_empty_info();
+        info.info_add_depth(depth as u8);
+        if let Some(idx) = multipv {
+            info.info_add_multipv(idx);
+        }
+        info.info_add_time(self.time_manager.elapsed().as_millis() as u64);
+        info.info_add_nodes(self.searches);
+        if evaluate::is_mate_score(eval){
+            info.info_add_score_mate(evaluate::to_mate(eval));
+        }else {
+            info.info_add_score_cp(eval);
+        }
+        info.info_add_hashfull(self.traspos_table.get_permill_fill());
+        if !line.is_empty(){
+            info.info_add_pv(line);
+        }
+        self.tx.send(info).err();
+    }
+
+    fn should_stop(&mut self) -> bool{
+        if self.stop.load(Ordering::Relaxed){
+            return true;
+        }
+        if let Some(limit) = self.node_limit {
+            if self.searches >= limit{
+                return true;
+            }
+        }
+        self.update_ponder();
+        !self.pondering && self.time_manager.hard_limit_reached()
+    }
+
+    // the clock starts when the gui sends ponderhit, there are no time limits while pondering
+    fn update_ponder(&mut self){
+        if self.pondering && !self.ponder.load(Ordering::Relaxed){
+            self.pondering = false;
+            self.time_manager.restart_clock();
+        }
+    }
+
+    // the move expected from the opponent after the best move, taken from the last principal variation
+    pub fn ponder_move(&self, best_move: Move) -> Option<Move>{
+        match self.best_line.first() {
+            Some(mv) if mv.get_hash() == best_move.get_hash() => self.best_line.get(1).copied(),
+            _ => None
+        }
+    }
+
+    // flag shared with the engine so a running search can be aborted from another thread
+    pub fn stop_handle(&self) -> Arc<AtomicBool>{
+        self.stop.clone()
+    }
+
+    // set while searching on the opponents time, cleared by the engine on ponderhit
+    pub fn ponder_handle(&self) -> Arc<AtomicBool>{
+        self.ponder.clone()
     }
 
     pub fn set_search_moves(&mut self, moves: Option<Vec<String>>){
         self.search_moves = moves;
     }
 
+    pub fn set_multi_pv(&mut self, lines: usize){
+        self.multi_pv = lines;
+    }
+
+    pub fn set_node_limit(&mut self, nodes: Option<u64>){
+        self.node_limit = nodes;
+    }
+
+    // search for a mate in the given number of moves, pruning that could hide a mate is turned off
+    pub fn set_mate_limit(&mut self, moves: Option<u8>){
+        self.mate_limit = moves;
+    }
+
+    pub fn set_threads(&mut self, threads: usize){
+        self.threads = threads.max(1);
+    }
+
+    pub fn set_time_manager(&mut self, time_manager: TimeManager){
+        self.time_manager = time_manager;
+    }
+
     pub fn reset(&mut self){
         self.traspos_table.clear();
     }
+
+    pub fn resize_hash(&mut self, hash_size_mb: usize){
+        self.traspos_table = Arc::new(TranspositionsTable::with_size_mb(hash_size_mb));
+    }
+
+    pub fn save_hash(&self, path: &str) -> io::Result<usize>{
+        self.traspos_table.save(path)
+    }
+
+    pub fn load_hash(&self, path: &str) -> io::Result<usize>{
+        self.traspos_table.load(path)
+    }
 }
 
 #[cfg(test)]
 mod test{
     use std::sync::mpsc;
 
-    use crate::{board::Board, engine::searcher::Searcher, movegeneration::singlemove::{Move, MoveType}, uci::uci_message::UciMessage};
+    use crate::{board::Board, engine::{evaluate, searcher::Searcher, time_manager::TimeManager}, movegeneration::singlemove::{Move, MoveType}, uci::uci_message::UciMessage};
 
 
     #[test]
@@ -360,7 +613,7 @@ mod test{
         let mut board = Board::from_fen(&fen);
         let (tx, _) = mpsc::channel::<UciMessage>();
  
-        let mut searcher = Searcher::new(5000000, tx);
+        let mut searcher = Searcher::new(16, tx);
         board.make_move(Move::new(4, 3, MoveType::Normal));
         board.make_move(Move::new(11, 18, MoveType::Normal));
         board.make_move(Move::new(52, 25, MoveType::Normal));
@@ -368,4 +621,109 @@ mod test{
         board.make_move(Move::new(2, 0, MoveType::Normal));
         assert_eq!(searcher.search_stable_pos(&mut board, 74, 174), 174);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn node_limit_is_reproducible(){
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let mut results = vec![];
+        for threads in [1, 4]{
+            let mut board = Board::from_fen(fen);
+            let (tx, _) = mpsc::channel::<UciMessage>();
+            let mut searcher = Searcher::new(4, tx);
+            searcher.set_threads(threads);
+            searcher.set_time_manager(TimeManager::infinite());
+            searcher.set_node_limit(Some(20000));
+            let (mv, score) = searcher.iterative_deepening(&mut board, None);
+            // the limit is checked after each move so the search may overshoot by a few nodes
+            assert!(searcher.searches < 20000 + 100);
+            results.push((mv.get_hash(), score));
+        }
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn mate_limit_finds_mate(){
+        let fen = "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1";
+        let mut board = Board::from_fen(fen);
+        let (tx, _) = mpsc::channel::<UciMessage>();
+        let mut searcher = Searcher::new(4, tx);
+        searcher.set_time_manager(TimeManager::infinite());
+        searcher.set_mate_limit(Some(1));
+        let (mv, score) = searcher.iterative_deepening(&mut board, None);
+        assert_eq!(mv.long_algebraic_notation(), "a1a8");
+        assert_eq!(evaluate::to_mate(score), 1);
+    }
+
+    #[test]
+    fn multi_pv_reports_distinct_lines(){
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let mut board = Board::from_fen(fen);
+        let (tx, rx) = mpsc::channel::<UciMessage>();
+        let mut searcher = Searcher::new(4, tx);
+        searcher.set_time_manager(TimeManager::infinite());
+        searcher.set_multi_pv(3);
+        searcher.iterative_deepening(&mut board, Some(3));
+        let first_moves: Vec<(u16, String)> = rx.try_iter().filter_map(|msg| {
+            match msg {
+                UciMessage::Info { depth: Some(3), multipv: Some(idx), pv: Some(pv), .. } => Some((idx, pv[0].long_algebraic_notation())),
+                _ => None
+            }
+        }).collect();
+        assert_eq!(first_moves.iter().map(|(idx, _)| *idx).collect::<Vec<u16>>(), vec![1, 2, 3]);
+        assert_ne!(first_moves[0].1, first_moves[1].1);
+        assert_ne!(first_moves[0].1, first_moves[2].1);
+        assert_ne!(first_moves[1].1, first_moves[2].1);
+    }
+
+    #[test]
+    fn pv_lines_are_complete_and_legal(){
+        let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let mut board = Board::from_fen(fen);
+        let (tx, rx) = mpsc::channel::<UciMessage>();
+        let mut searcher = Searcher::new(4, tx);
+        searcher.set_time_manager(TimeManager::infinite());
+        searcher.iterative_deepening(&mut board, Some(6));
+        let lines: Vec<(u8, Vec<Move>)> = rx.try_iter().filter_map(|msg| {
+            match msg {
+                UciMessage::Info { depth: Some(depth), pv: Some(pv), .. } => Some((depth, pv)),
+                _ => None
+            }
+        }).collect();
+        assert_eq!(lines.len(), 6);
+        for (depth, line) in lines{
+            assert!(line.len() >= depth as usize);
+            let mut replay = Board::from_fen(fen);
+            for mv in line{
+                let legal = replay.get_possible_moves_turn().iter().any(|other| other.get_hash() == mv.get_hash());
+                assert!(legal, "{} is not legal at depth {}", mv.long_algebraic_notation(), depth);
+                replay.make_move(mv);
+            }
+        }
+    }
+
+    #[test]
+    fn lazy_smp_finds_mate(){
+        let fen = "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1";
+        let mut board = Board::from_fen(fen);
+        let (tx, _) = mpsc::channel::<UciMessage>();
+        let mut searcher = Searcher::new(4, tx);
+        searcher.set_time_manager(TimeManager::infinite());
+        searcher.set_threads(4);
+        let (mv, score) = searcher.iterative_deepening(&mut board, Some(4));
+        assert_eq!(mv.long_algebraic_notation(), "a1a8");
+        assert_eq!(evaluate::to_mate(score), 1);
+        assert_eq!(board.get_zobrist_hash(), Board::from_fen(fen).get_zobrist_hash());
+    }
+
+    #[test]
+    fn fifty_move_rule_is_a_draw(){
+        // black is lost but every king move ends the game by the fifty move rule
+        let fen = "7k/8/8/8/8/8/8/K6Q b - - 99 120";
+        let mut board = Board::from_fen(fen);
+        let (tx, _) = mpsc::channel::<UciMessage>();
+        let mut searcher = Searcher::new(4, tx);
+        searcher.set_time_manager(TimeManager::infinite());
+        let (_, score) = searcher.iterative_deepening(&mut board, Some(3));
+        assert_eq!(score, evaluate::draw_by_fifty_move_rule());
+    }
+}
@@ -0,0 +1,216 @@
+use std::{fs, sync::mpsc::{self, Receiver}, time::{Duration, Instant}};
+
+use crate::{board::Board, epd::{self, EpdRecord}, movegeneration::singlemove::Move, uci::uci_message::UciMessage};
+
+use super::{searcher::Searcher, time_manager::TimeManager};
+
+const DEFAULT_MOVE_TIME: u64 = 1000;
+const DEFAULT_HASH_MB: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Limit{
+    Time(u64),
+    Depth(u8),
+    Nodes(u64)
+}
+
+// what counts as solving a position, sts files give points for several moves in the c0 comment
+struct Expectation{
+    best: Vec<Move>,
+    avoid: Vec<Move>,
+    points: Vec<(Move, u32)>
+}
+
+impl Expectation {
+    fn from_record(record: &EpdRecord) -> Result<Expectation, String>{
+        let mut board = record.board().map_err(|err| err.to_string())?;
+        let points = record.operation("c0").and_then(|operands| operands.first())
+            .map(|comment| parse_points(&mut board, comment))
+            .unwrap_or_default();
+        Ok(Expectation { best: record.moves("bm")?, avoid: record.moves("am")?, points })
+    }
+
+    fn solved_by(&self, mv: Move) -> bool{
+        let contains = |moves: &Vec<Move>| moves.iter().any(|other| other.get_hash() == mv.get_hash());
+        (self.best.is_empty() || contains(&self.best)) && !contains(&self.avoid)
+    }
+
+    fn points(&self, mv: Move) -> u32{
+        self.points.iter().find(|(other, _)| other.get_hash() == mv.get_hash()).map_or(0, |(_, points)| *points)
+    }
+
+    fn max_points(&self) -> u32{
+        self.points.iter().map(|(_, points)| *points).max().unwrap_or(0)
+    }
+}
+
+// "f5=10, Be5+=2, Bf2=3", anything that doesn't look like that gives no points
+fn parse_points(board: &mut Board, comment: &str) -> Vec<(Move, u32)>{
+    comment.split(',')
+        .filter_map(|pair| {
+            let (mv, points) = pair.trim().rsplit_once('=')?;
+            Some((epd::parse_move(board, mv)?, points.parse().ok()?))
+        })
+        .collect()
+}
+
+struct PositionResult{
+    id: String,
+    expected: String,
+    found: Move,
+    solved: bool,
+    points: Option<(u32, u32)>,
+    // when the best move became and stayed a solution
+    time_to_solution: Option<u64>,
+    depth: u8,
+    nodes: u64
+}
+
+// the search is done, go through its info messages to find the depth reached and since when the pv started with a solution
+fn search_progress(rx: &Receiver<UciMessage>, expectation: &Expectation, found: Move, elapsed: u64) -> (Option<u64>, u8){
+    let mut solved_since = None;
+    let mut reached = 0;
+    for message in rx.try_iter(){
+        let UciMessage::Info { depth: Some(depth), time, pv: Some(pv), multipv, .. } = message else { continue };
+        if multipv.is_some_and(|idx| idx > 1){
+            continue;
+        }
+        reached = depth;
+        match pv.first() {
+            Some(mv) if expectation.solved_by(*mv) => solved_since = solved_since.or(time),
+            _ => solved_since = None
+        }
+    }
+    // the move can change in an unfinished iteration that never sent its pv
+    let time_to_solution = expectation.solved_by(found).then(|| solved_since.unwrap_or(elapsed).min(elapsed));
+    (time_to_solution, reached)
+}
+
+fn run_position(searcher: &mut Searcher, rx: &Receiver<UciMessage>, record: &EpdRecord, limit: Limit) -> Result<PositionResult, String>{
+    let mut board = record.board().map_err(|err| err.to_string())?;
+    let expectation = Expectation::from_record(record)?;
+    searcher.reset();
+    searcher.set_node_limit(None);
+    let max_depth = match limit {
+        Limit::Time(ms) => {
+            searcher.set_time_manager(TimeManager::fixed(Duration::from_millis(ms)));
+            None
+        },
+        Limit::Depth(depth) => {
+            searcher.set_time_manager(TimeManager::infinite());
+            Some(depth)
+        },
+        Limit::Nodes(nodes) => {
+            searcher.set_time_manager(TimeManager::infinite());
+            searcher.set_node_limit(Some(nodes));
+            None
+        }
+    };
+    let start = Instant::now();
+    let (found, _score) = searcher.iterative_deepening(&mut board, max_depth);
+    let elapsed = start.elapsed().as_millis() as u64;
+    let (time_to_solution, depth) = search_progress(rx, &expectation, found, elapsed);
+
+    let mut expected: Vec<String> = expectation.best.iter().map(|mv| mv.long_algebraic_notation()).collect();
+    expected.extend(expectation.avoid.iter().map(|mv| format!("!{}", mv.long_algebraic_notation())));
+    Ok(PositionResult {
+        id: record.id().unwrap_or_default().into(),
+        expected: expected.join(" "),
+        found,
+        solved: expectation.solved_by(found),
+        points: (!expectation.points.is_empty()).then(|| (expectation.points(found), expectation.max_points())),
+        time_to_solution,
+        depth,
+        nodes: searcher.searches
+    })
+}
+
+// epd <suite.epd> [--time MS | --depth N | --nodes N] [--hash MB] [--threads N], positions without bm or am are only searched
+pub fn run(args: &[String]) -> Result<(), String>{
+    let usage = "usage: epd <suite.epd> [--time MS | --depth N | --nodes N] [--hash MB] [--threads N]";
+    let [input, flags @ ..] = args else { return Err(usage.into()) };
+    let mut limit = Limit::Time(DEFAULT_MOVE_TIME);
+    let (mut hash, mut threads) = (DEFAULT_HASH_MB, 1);
+    for flag in flags.chunks(2){
+        let value = flag.get(1).ok_or(usage)?;
+        let invalid = format!("invalid value for {}: {}", flag[0], value);
+        match flag[0].as_str() {
+            "--time" => limit = Limit::Time(value.parse().map_err(|_| invalid)?),
+            "--depth" => limit = Limit::Depth(value.parse().map_err(|_| invalid)?),
+            "--nodes" => limit = Limit::Nodes(value.parse().map_err(|_| invalid)?),
+            "--hash" => hash = value.parse().map_err(|_| invalid)?,
+            "--threads" => threads = value.parse().map_err(|_| invalid)?,
+            _ => return Err(usage.into())
+        }
+    }
+    let suite = fs::read_to_string(input).map_err(|err| format!("could not read {}: {}", input, err))?;
+    let (tx, rx) = mpsc::channel();
+    let mut searcher = Searcher::new(hash, tx);
+    searcher.set_threads(threads);
+
+    println!("{:>4}  {:<24} {:<16} {:<6} {:<6} {:>8} {:>5} {:>10}", "#", "id", "expected", "found", "result", "time ms", "depth", "nodes");
+    let mut results = vec![];
+    for (idx, line) in suite.lines().enumerate().filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#')){
+        let result = EpdRecord::parse(line).and_then(|record| run_position(&mut searcher, &rx, &record, limit));
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                println!("{:>4}  skipped: {}", idx + 1, err);
+                continue;
+            }
+        };
+        let outcome = match result.points {
+            Some((points, max)) => format!("{}/{}", points, max),
+            None if result.solved => "ok".into(),
+            None => "fail".into()
+        };
+        let time = result.time_to_solution.map_or("-".into(), |time| time.to_string());
+        println!("{:>4}  {:<24} {:<16} {:<6} {:<6} {:>8} {:>5} {:>10}",
+            idx + 1, result.id, result.expected, result.found.long_algebraic_notation(), outcome, time, result.depth, result.nodes);
+        results.push(result);
+    }
+
+    let solved: Vec<&PositionResult> = results.iter().filter(|result| result.solved).collect();
+    let total_time: u64 = solved.iter().filter_map(|result| result.time_to_solution).sum();
+    println!("solved {} of {} positions, total time to solution {} ms", solved.len(), results.len(), total_time);
+    let scored: Vec<(u32, u32)> = results.iter().filter_map(|result| result.points).collect();
+    if !scored.is_empty() {
+        let (points, max) = scored.iter().fold((0, 0), |(points, max), (p, m)| (points + p, max + m));
+        println!("score {} of {} points", points, max);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests{
+    use std::sync::mpsc;
+
+    use crate::{engine::searcher::Searcher, epd::EpdRecord};
+
+    use super::{run_position, Expectation, Limit};
+
+    #[test]
+    fn sts_points(){
+        let record = EpdRecord::parse(r#"6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; c0 "Ra8#=10, Kf1=2, Rb1=1";"#).unwrap();
+        let expectation = Expectation::from_record(&record).unwrap();
+        assert_eq!(expectation.max_points(), 10);
+        assert_eq!(expectation.points(expectation.best[0]), 10);
+        assert_eq!(expectation.points.len(), 3);
+    }
+
+    #[test]
+    fn solves_mate_in_one(){
+        let (tx, rx) = mpsc::channel();
+        let mut searcher = Searcher::new(4, tx);
+        let record = EpdRecord::parse(r#"6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; am Rb1; id "back rank";"#).unwrap();
+        let result = run_position(&mut searcher, &rx, &record, Limit::Depth(3)).unwrap();
+        assert!(result.solved);
+        assert_eq!(result.id, "back rank");
+        assert_eq!(result.found.long_algebraic_notation(), "a1a8");
+        assert!(result.time_to_solution.is_some());
+        assert!(result.depth >= 1);
+
+        let record = EpdRecord::parse("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - am Ra8#;").unwrap();
+        assert!(!run_position(&mut searcher, &rx, &record, Limit::Nodes(5000)).unwrap().solved);
+    }
+}
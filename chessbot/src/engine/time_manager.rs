@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+use crate::movegeneration::singlemove::Move;
+
+pub const DEFAULT_SEARCH_TIME: Duration = Duration::from_millis(3000);
+
+// assumed number of moves left in the game when the gui doesn't send movestogo
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+// time kept in reserve for communication with the gui
+const MOVE_OVERHEAD: u64 = 50;
+const MIN_SEARCH_TIME: u64 = 10;
+// the hard limit may never use more than this part of the remaining clock
+const MAX_CLOCK_USAGE_PERCENT: u64 = 40;
+const HARD_LIMIT_FACTOR: u64 = 4;
+// percent of the soft limit to use depending on how many iterations in a row the best move has stayed the same
+const STABILITY_SCALE: [u64; 6] = [200, 140, 110, 90, 80, 70];
+const SCORE_DROP_MARGIN: i32 = 30;
+const SCORE_DROP_SCALE: u64 = 150;
+
+pub struct TimeManager{
+    start_time: Instant,
+    soft_limit: Duration,
+    hard_limit: Duration,
+    adjustable: bool,
+    previous_best: Option<u32>,
+    previous_score: Option<i32>,
+    stability: usize,
+    scale: u64
+}
+
+impl TimeManager {
+    // search for exactly `duration`, used for `go movetime` and when no clock is given
+    pub fn fixed(duration: Duration) -> TimeManager{
+        TimeManager{
+            start_time: Instant::now(),
+            soft_limit: duration,
+            hard_limit: duration,
+            adjustable: false,
+            previous_best: None,
+            previous_score: None,
+            stability: 0,
+            scale: 100
+        }
+    }
+
+    // analysis mode, the search only ends when the gui sends stop
+    pub fn infinite() -> TimeManager{
+        TimeManager::fixed(Duration::MAX)
+    }
+
+    // allocate a soft and a hard limit from the remaining clock, all values in milliseconds
+    pub fn from_clock(time_left: u64, increment: u64, moves_to_go: Option<u64>) -> TimeManager{
+        let moves_to_go = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).clamp(1, DEFAULT_MOVES_TO_GO);
+        let usable = time_left.saturating_sub(MOVE_OVERHEAD);
+        let max_time = (usable * MAX_CLOCK_USAGE_PERCENT / 100).max(MIN_SEARCH_TIME);
+
+        let soft = (usable / moves_to_go + increment * 3 / 4).clamp(MIN_SEARCH_TIME, max_time);
+        let hard = (soft * HARD_LIMIT_FACTOR).min(max_time);
+        TimeManager{
+            start_time: Instant::now(),
+            soft_limit: Duration::from_millis(soft),
+            hard_limit: Duration::from_millis(hard),
+            adjustable: moves_to_go > 1,
+            previous_best: None,
+            previous_score: None,
+            stability: 0,
+            scale: 100
+        }
+    }
+
+    pub fn start(&mut self){
+        self.start_time = Instant::now();
+        self.previous_best = None;
+        self.previous_score = None;
+        self.stability = 0;
+        self.scale = 100;
+    }
+
+    pub fn restart_clock(&mut self){
+        self.start_time = Instant::now();
+    }
+
+    pub fn elapsed(&self) -> Duration{
+        self.start_time.elapsed()
+    }
+
+    // the search has to stop immediately, even in the middle of an iteration
+    pub fn hard_limit_reached(&self) -> bool{
+        self.start_time.elapsed() >= self.hard_limit
+    }
+
+    // no new iteration should be started
+    pub fn soft_limit_reached(&self) -> bool{
+        let elapsed = self.start_time.elapsed();
+        if !self.adjustable{
+            return elapsed >= self.soft_limit;
+        }
+        let soft = self.soft_limit.mul_f64(self.scale as f64 / 100.0).min(self.hard_limit);
+        elapsed >= soft
+    }
+
+    // update the stability of the best move and the score after a finished iteration
+    pub fn update_iteration(&mut self, best_move: Move, score: i32){
+        if self.previous_best == Some(best_move.get_hash()){
+            self.stability = (self.stability + 1).min(STABILITY_SCALE.len() - 1);
+        }else {
+            self.stability = 0;
+        }
+        self.scale = STABILITY_SCALE[self.stability];
+        if let Some(previous) = self.previous_score {
+            if score < previous - SCORE_DROP_MARGIN {
+                self.scale = self.scale * SCORE_DROP_SCALE / 100;
+            }
+        }
+        self.previous_best = Some(best_move.get_hash());
+        self.previous_score = Some(score);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::time::Duration;
+
+    use crate::movegeneration::singlemove::{Move, MoveType};
+
+    use super::TimeManager;
+
+    #[test]
+    fn clock_allocation_within_limits(){
+        let tm = TimeManager::from_clock(60_000, 0, None);
+        assert!(tm.soft_limit < tm.hard_limit);
+        assert!(tm.hard_limit <= Duration::from_millis(60_000 * 40 / 100));
+
+        let tm = TimeManager::from_clock(100, 0, None);
+        assert!(tm.hard_limit <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn moves_to_go_uses_more_time(){
+        let long = TimeManager::from_clock(60_000, 0, Some(40));
+        let short = TimeManager::from_clock(60_000, 0, Some(5));
+        assert!(short.soft_limit > long.soft_limit);
+    }
+
+    #[test]
+    fn stable_best_move_shortens_search(){
+        let mut tm = TimeManager::from_clock(60_000, 1_000, None);
+        let mv = Move::new(12, 28, MoveType::Pawndubblemove);
+        tm.update_iteration(mv, 20);
+        let unstable = tm.scale;
+        for _ in 0..5{
+            tm.update_iteration(mv, 20);
+        }
+        assert!(tm.scale < unstable);
+
+        tm.update_iteration(mv, -100);
+        assert!(tm.scale > 100 * 70 / 100);
+    }
+}
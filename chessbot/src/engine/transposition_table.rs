@@ -1,7 +1,16 @@
-use crate::movegeneration::singlemove::Move;
+use std::{fs::File, io::{self, BufReader, BufWriter, Read, Write}, path::Path, sync::atomic::{AtomicU64, AtomicU8, Ordering}};
+
+use crate::{board::zobrist, movegeneration::singlemove::Move};
 
 use super::evaluate;
 
+// data layout: value 32 bits | best move 20 bits | depth 8 bits | flag 2 bits
+const MOVE_SHIFT: u64 = 32;
+const MOVE_MASK: u64 = (1 << 20) - 1;
+const DEPTH_SHIFT: u64 = 52;
+const DEPTH_MASK: u64 = 0xFF;
+const FLAG_SHIFT: u64 = 60;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum TranspositionsFlag{
     Exact = 0,
@@ -9,88 +18,291 @@ pub enum TranspositionsFlag{
     UpperBound = 2,
 }
 
+impl TranspositionsFlag {
+    fn from_bits(bits: u64) -> TranspositionsFlag{
+        match bits & 0b11 {
+            0 => TranspositionsFlag::Exact,
+            1 => TranspositionsFlag::LowerBound,
+            _ => TranspositionsFlag::UpperBound,
+        }
+    }
+}
+
+// the key is stored xored with the data so a torn write from another thread is detected as a miss
 struct Entry{
-    zobrist: u64,
-    depth: usize,
-    flag: TranspositionsFlag,
-    value: i32,
-    best: Option<Move>
+    key: AtomicU64,
+    data: AtomicU64
 }
 
 impl Entry {
-    fn new(zobrits: u64, depth: usize, flag: TranspositionsFlag, value: i32, best: Option<Move>) -> Entry{
+    fn new() -> Entry{
         Entry{
-            zobrist: zobrits,
-            depth,
-            flag,
-            value,
-            best
+            key: AtomicU64::new(0),
+            data: AtomicU64::new(0)
         }
     }
+
+    fn load(&self, zobrist: u64) -> Option<u64>{
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed);
+        if key ^ data == zobrist && (key | data) != 0{
+            Some(data)
+        }else {
+            None
+        }
+    }
+
+    fn is_empty(&self) -> bool{
+        self.key.load(Ordering::Relaxed) == 0 && self.data.load(Ordering::Relaxed) == 0
+    }
+
+    fn store(&self, zobrist: u64, data: u64){
+        self.key.store(zobrist ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+
+    fn clear(&self){
+        self.key.store(0, Ordering::Relaxed);
+        self.data.store(0, Ordering::Relaxed);
+    }
+
+    fn depth(&self) -> usize{
+        unpack_depth(self.data.load(Ordering::Relaxed))
+    }
+}
+
+fn pack(depth: usize, flag: TranspositionsFlag, value: i32, best: Option<Move>) -> u64{
+    let mv = best.map(|mv| mv.get_hash() as u64 & MOVE_MASK).unwrap_or(0);
+    (value as u32 as u64) | mv << MOVE_SHIFT | (depth.min(DEPTH_MASK as usize) as u64) << DEPTH_SHIFT | (flag as u64) << FLAG_SHIFT
+}
+
+fn unpack_value(data: u64) -> i32{
+    data as u32 as i32
+}
+
+fn unpack_move(data: u64) -> Option<Move>{
+    let mv = (data >> MOVE_SHIFT) & MOVE_MASK;
+    if mv == 0 { None } else { Some(Move::from_hash(mv as u32)) }
+}
+
+fn unpack_depth(data: u64) -> usize{
+    ((data >> DEPTH_SHIFT) & DEPTH_MASK) as usize
+}
+
+// saved tables: magic, version, zobrist fingerprint, generation, entry count,
+// then the position key, data and generation of every used entry, all little endian.
+// the version changes with the data layout
+const FILE_MAGIC: &[u8; 8] = b"CHBOTTT\0";
+const FILE_VERSION: u32 = 1;
+const FILE_HEADER_SIZE: usize = 8 + 4 + 8 + 1 + 8;
+const FILE_RECORD_SIZE: usize = 8 + 8 + 1;
+
+const BUCKET_SIZE: usize = 3;
+// entries sampled for hashfull, the uci protocol reports it in permill
+const HASHFULL_SAMPLE: usize = 1000;
+// an entry for the same position is only replaced by a search that is at most this much shallower
+const SAME_KEY_DEPTH_MARGIN: usize = 2;
+
+// one cache line: the first slots keep the deepest results, the last one always takes the newest.
+// the generation of the search that wrote each slot is kept in a byte of its own
+#[repr(align(64))]
+struct Bucket{
+    entries: [Entry; BUCKET_SIZE],
+    generations: AtomicU64
+}
+
+impl Bucket {
+    fn new() -> Bucket{
+        Bucket { entries: [Entry::new(), Entry::new(), Entry::new()], generations: AtomicU64::new(0) }
+    }
+
+    fn generation(&self, slot: usize) -> u8{
+        (self.generations.load(Ordering::Relaxed) >> (slot * 8)) as u8
+    }
+
+    // racing writers may lose an update, the generation is only a hint for replacement
+    fn set_generation(&self, slot: usize, generation: u8){
+        let generations = self.generations.load(Ordering::Relaxed);
+        let generations = generations & !(0xFF << (slot * 8)) | (generation as u64) << (slot * 8);
+        self.generations.store(generations, Ordering::Relaxed);
+    }
+
+    fn find(&self, zobrist: u64) -> Option<(usize, u64)>{
+        self.entries.iter().enumerate().find_map(|(slot, entry)| entry.load(zobrist).map(|data| (slot, data)))
+    }
+
+    fn clear(&self){
+        self.entries.iter().for_each(|entry| entry.clear());
+        self.generations.store(0, Ordering::Relaxed);
+    }
 }
 
+// shared between all search threads, every method only needs a shared reference
 pub struct TranspositionsTable{
-    hash_table: Vec<Option<Entry>>,
-    size: usize,
-    nbr_filled: usize
+    buckets: Vec<Bucket>,
+    // bumped for every new search so entries from earlier searches can be replaced first
+    generation: AtomicU8
 }
 // https://web.archive.org/web/20071031100051/http://www.brucemo.com/compchess/programming/hashing.htm
 impl TranspositionsTable {
-    pub fn new(size: usize) -> TranspositionsTable{
-        // println!("entry size: {}, nbr_entries: {}", std::mem::size_of::<Entry>(), (32*1024*2024)/std::mem::size_of::<Entry>());
+    pub fn new(buckets: usize) -> TranspositionsTable{
         TranspositionsTable{
-            hash_table: (0..size).map(|_| None).collect(),
-            size,
-            nbr_filled: 0
+            buckets: (0..buckets.max(1)).map(|_| Bucket::new()).collect(),
+            generation: AtomicU8::new(0)
         }
     }
 
-    pub fn clear(&mut self){
-        self.nbr_filled = 0;
-        // self.hash_table = (0..self.size).map(|_| None).collect();
-        self.hash_table.iter_mut().for_each(|entry| *entry = None);
+    pub fn with_size_mb(megabytes: usize) -> TranspositionsTable{
+        TranspositionsTable::new(megabytes * 1024 * 1024 / std::mem::size_of::<Bucket>())
+    }
+
+    pub fn clear(&self){
+        self.generation.store(0, Ordering::Relaxed);
+        self.buckets.iter().for_each(|bucket| bucket.clear());
+    }
+
+    // entries written before this are stale and replaced before anything from the current search
+    pub fn new_search(&self){
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn bucket(&self, zobrist: u64) -> &Bucket{
+        &self.buckets[(zobrist as usize) % self.buckets.len()]
     }
 
     pub fn lookup_eval(&self, zobrist: u64, depth: usize, ply: usize, alpha: i32, beta: i32) -> Option<i32> {
-        if let Some(entry) = &self.hash_table[(zobrist as usize) % self.size]{
-            if entry.zobrist == zobrist{
-                if entry.depth >= depth{
-                    let score = self.retrive_score(entry.value, ply);
-                    if entry.flag == TranspositionsFlag::Exact{
-                        return Some(score)
-                    }else if entry.flag == TranspositionsFlag::UpperBound && entry.value <= alpha {
-                        return Some(score)
-                    }else if entry.flag == TranspositionsFlag::LowerBound && entry.value >= beta {
-                        return Some(score)
-                    }
+        if let Some((_, data)) = self.bucket(zobrist).find(zobrist){
+            if unpack_depth(data) >= depth{
+                // mate scores are stored relative to the node, the bounds are compared at this ply
+                let score = self.retrive_score(unpack_value(data), ply);
+                let flag = TranspositionsFlag::from_bits(data >> FLAG_SHIFT);
+                if flag == TranspositionsFlag::Exact{
+                    return Some(score)
+                }else if flag == TranspositionsFlag::UpperBound && score <= alpha {
+                    return Some(score)
+                }else if flag == TranspositionsFlag::LowerBound && score >= beta {
+                    return Some(score)
                 }
             }
         }
         None
     }
 
-    pub fn record_entry(&mut self, zobrist: u64, depth: usize, ply: usize, value: i32, flag: TranspositionsFlag, mut best_move: Option<Move>){
+    pub fn record_entry(&self, zobrist: u64, depth: usize, ply: usize, value: i32, flag: TranspositionsFlag, mut best_move: Option<Move>){
         if let Some(mv) = best_move{
             if mv.is_null_move(){
                 best_move = None;
             }
         }
-        let entry = Entry::new(zobrist, depth, flag, self.store_score(value, ply), best_move);
-        if let None = self.hash_table[(zobrist as usize) % self.size] { self.nbr_filled+=1 };
-        self.hash_table[(zobrist as usize) % self.size] = Some(entry);
+        let generation = self.generation.load(Ordering::Relaxed);
+        let bucket = self.bucket(zobrist);
+        let slot = match bucket.find(zobrist) {
+            Some((slot, data)) => {
+                let current = bucket.generation(slot) == generation;
+                if current && flag != TranspositionsFlag::Exact && depth + SAME_KEY_DEPTH_MARGIN < unpack_depth(data) {
+                    return;
+                }
+                // a fail low has no best move, keep the one found earlier for move ordering
+                best_move = best_move.or(unpack_move(data));
+                slot
+            },
+            None => self.replacement_slot(bucket, depth, generation)
+        };
+        bucket.entries[slot].store(zobrist, pack(depth, flag, self.store_score(value, ply), best_move));
+        bucket.set_generation(slot, generation);
     }
 
-    pub fn get_best_move(&self, zobrist: u64) -> Option<Move>{
-        if let Some(entry) = &self.hash_table[(zobrist as usize) % self.size]{
-            if entry.zobrist == zobrist{
-                return entry.best;
-            }
+    // the shallowest of the depth preferred slots if the new entry is at least as deep or the slot is stale,
+    // otherwise the always replace slot
+    fn replacement_slot(&self, bucket: &Bucket, depth: usize, generation: u8) -> usize{
+        let always_replace = BUCKET_SIZE - 1;
+        let worth = |slot: usize| {
+            let entry = &bucket.entries[slot];
+            if entry.is_empty() || bucket.generation(slot) != generation { None } else { Some(entry.depth()) }
+        };
+        let victim = (0..always_replace).min_by_key(|slot| worth(*slot)).unwrap_or(always_replace);
+        match worth(victim) {
+            Some(victim_depth) if victim_depth > depth => always_replace,
+            _ => victim
         }
-        None
     }
 
+    pub fn get_best_move(&self, zobrist: u64) -> Option<Move>{
+        self.bucket(zobrist).find(zobrist).and_then(|(_, data)| unpack_move(data))
+    }
+
+    // part of a sample of the table written by the current search, in permill
     pub fn get_permill_fill(&self) -> u16{
-        (self.nbr_filled * 1000 / self.size) as u16
+        let generation = self.generation.load(Ordering::Relaxed);
+        let buckets = self.buckets.len().min(HASHFULL_SAMPLE / BUCKET_SIZE);
+        let filled = self.buckets[..buckets].iter()
+            .map(|bucket| (0..BUCKET_SIZE).filter(|slot| !bucket.entries[*slot].is_empty() && bucket.generation(*slot) == generation).count())
+            .sum::<usize>();
+        (filled * 1000 / (buckets * BUCKET_SIZE)) as u16
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<usize>{
+        let entries: Vec<(u64, u64, u8)> = self.buckets.iter()
+            .flat_map(|bucket| (0..BUCKET_SIZE).filter_map(move |slot| {
+                let entry = &bucket.entries[slot];
+                let (key, data) = (entry.key.load(Ordering::Relaxed), entry.data.load(Ordering::Relaxed));
+                (key | data != 0).then_some((key ^ data, data, bucket.generation(slot)))
+            }))
+            .collect();
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(FILE_MAGIC)?;
+        file.write_all(&FILE_VERSION.to_le_bytes())?;
+        file.write_all(&zobrist::key_fingerprint().to_le_bytes())?;
+        file.write_all(&[self.generation.load(Ordering::Relaxed)])?;
+        file.write_all(&(entries.len() as u64).to_le_bytes())?;
+        for (zobrist, data, generation) in entries.iter(){
+            file.write_all(&zobrist.to_le_bytes())?;
+            file.write_all(&data.to_le_bytes())?;
+            file.write_all(&[*generation])?;
+        }
+        file.flush()?;
+        Ok(entries.len())
+    }
+
+    // replaces the content with a saved table, the entries are rehashed so the sizes don't have to match
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> io::Result<usize>{
+        let mut file = BufReader::new(File::open(path)?);
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != FILE_MAGIC {
+            return Err(invalid("not a saved hash table"));
+        }
+        let version = read_u32(&mut file)?;
+        if version != FILE_VERSION {
+            return Err(invalid(&format!("hash file version {} is not supported, expected {}", version, FILE_VERSION)));
+        }
+        if read_u64(&mut file)? != zobrist::key_fingerprint() {
+            return Err(invalid("hash file was written with different zobrist keys"));
+        }
+        let generation = read_u8(&mut file)?;
+        let count = read_u64(&mut file)?;
+        // a truncated or padded file is rejected before the table is touched
+        let expected_len = (FILE_HEADER_SIZE as u64).checked_add(count.checked_mul(FILE_RECORD_SIZE as u64).ok_or_else(|| invalid("invalid entry count"))?);
+        if expected_len != Some(file.get_ref().metadata()?.len()) {
+            return Err(invalid(&format!("hash file size doesn't match its {} entries", count)));
+        }
+        let records = (0..count)
+            .map(|_| Ok((read_u64(&mut file)?, read_u64(&mut file)?, read_u8(&mut file)?)))
+            .collect::<io::Result<Vec<(u64, u64, u8)>>>()?;
+
+        self.clear();
+        self.generation.store(generation, Ordering::Relaxed);
+        for (zobrist, data, entry_generation) in records{
+            let bucket = self.bucket(zobrist);
+            let slot = match bucket.find(zobrist) {
+                Some((slot, _)) => slot,
+                None => self.replacement_slot(bucket, unpack_depth(data), generation)
+            };
+            bucket.entries[slot].store(zobrist, data);
+            bucket.set_generation(slot, entry_generation);
+        }
+        Ok(count as usize)
     }
 
     fn store_score(&self, score: i32, ply: usize) -> i32{
@@ -108,4 +320,109 @@ impl TranspositionsTable {
         }
         score
     }
-}
\ No newline at end of file
+}
+
+fn read_u8(file: &mut impl Read) -> io::Result<u8>{
+    let mut bytes = [0; 1];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u32(file: &mut impl Read) -> io::Result<u32>{
+    let mut bytes = [0; 4];
+    file.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(file: &mut impl Read) -> io::Result<u64>{
+    let mut bytes = [0; 8];
+    file.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::movegeneration::singlemove::{Move, MoveType};
+
+    use crate::engine::evaluate;
+
+    use super::{Bucket, TranspositionsFlag, TranspositionsTable};
+
+    #[test]
+    fn record_and_lookup(){
+        let table = TranspositionsTable::new(1024);
+        let mv = Move::new(12, 28, MoveType::Pawndubblemove);
+        table.record_entry(0xDEADBEEF, 5, 0, -37, TranspositionsFlag::Exact, Some(mv));
+        assert_eq!(table.lookup_eval(0xDEADBEEF, 5, 0, -100, 100), Some(-37));
+        assert_eq!(table.lookup_eval(0xDEADBEEF, 6, 0, -100, 100), None);
+        assert_eq!(table.get_best_move(0xDEADBEEF).map(|mv| mv.get_hash()), Some(mv.get_hash()));
+        assert!(table.get_best_move(0xDEADBEEF + 1024).is_none());
+    }
+
+    #[test]
+    fn mate_bounds_at_ply(){
+        let table = TranspositionsTable::new(16);
+        // at most being mated 5 plies from the root, stored at ply 2 and read at ply 4 it is 7 plies
+        table.record_entry(1, 3, 2, evaluate::mate_ajusted_score(5), TranspositionsFlag::UpperBound, None);
+        let at_ply_4 = evaluate::mate_ajusted_score(7);
+        assert_eq!(table.lookup_eval(1, 3, 4, at_ply_4, at_ply_4 + 10), Some(at_ply_4));
+        assert_eq!(table.lookup_eval(1, 3, 4, at_ply_4 - 1, at_ply_4 + 10), None);
+    }
+
+    #[test]
+    fn replacement(){
+        assert_eq!(std::mem::size_of::<Bucket>(), 64);
+        let table = TranspositionsTable::new(1);
+        table.new_search();
+        table.record_entry(1, 10, 0, 0, TranspositionsFlag::Exact, None);
+        table.record_entry(2, 8, 0, 0, TranspositionsFlag::Exact, None);
+        // the shallow entries of the same search go to the always replace slot
+        for key in 3..10{
+            table.record_entry(key, 1, 0, 0, TranspositionsFlag::Exact, None);
+        }
+        assert!(table.lookup_eval(1, 10, 0, 0, 0).is_some());
+        assert!(table.lookup_eval(2, 8, 0, 0, 0).is_some());
+        assert!(table.lookup_eval(9, 1, 0, 0, 0).is_some());
+        assert_eq!(table.get_permill_fill(), 1000);
+
+        // entries of an older search make room even for shallow ones
+        table.new_search();
+        assert_eq!(table.get_permill_fill(), 0);
+        table.record_entry(10, 1, 0, 0, TranspositionsFlag::Exact, None);
+        table.record_entry(11, 1, 0, 0, TranspositionsFlag::Exact, None);
+        assert!(table.lookup_eval(10, 1, 0, 0, 0).is_some());
+        assert!(table.lookup_eval(11, 1, 0, 0, 0).is_some());
+        assert_eq!(table.get_permill_fill(), 666);
+    }
+
+    #[test]
+    fn save_and_load(){
+        let path = std::env::temp_dir().join(format!("chessbot-tt-{}.bin", std::process::id()));
+        let key = |idx: u64| idx.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let table = TranspositionsTable::new(1024);
+        table.new_search();
+        let mv = Move::new(12, 28, MoveType::Pawndubblemove);
+        for idx in 1..200{
+            table.record_entry(key(idx), 4, 0, idx as i32, TranspositionsFlag::Exact, Some(mv));
+        }
+        assert_eq!(table.save(&path).unwrap(), 199);
+
+        // a table of another size gets the same entries
+        let loaded = TranspositionsTable::new(512);
+        assert_eq!(loaded.load(&path).unwrap(), 199);
+        assert_eq!(loaded.lookup_eval(key(7), 4, 0, -100, 100), Some(7));
+        assert_eq!(loaded.get_best_move(key(7)).map(|mv| mv.get_hash()), Some(mv.get_hash()));
+        assert!((1..200).all(|idx| loaded.lookup_eval(key(idx), 4, 0, -1000, 1000) == Some(idx as i32)));
+
+        // keys from another build and truncated files are rejected and the table is kept
+        let bytes = std::fs::read(&path).unwrap();
+        let mut other_keys = bytes.clone();
+        other_keys[12] ^= 1;
+        std::fs::write(&path, other_keys).unwrap();
+        assert!(loaded.load(&path).is_err());
+        std::fs::write(&path, &bytes[..bytes.len() - 5]).unwrap();
+        assert!(loaded.load(&path).is_err());
+        assert!((1..200).all(|idx| loaded.lookup_eval(key(idx), 4, 0, -1000, 1000) == Some(idx as i32)));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{board::{fen::FenError, Board}, movegeneration::singlemove::Move};
+
+// extended position description, the first four fen fields followed by operations: opcode operand ...;
+#[derive(Debug, Clone)]
+pub struct EpdRecord{
+    pub fen: String,
+    pub operations: Vec<(String, Vec<String>)>
+}
+
+impl EpdRecord {
+    pub fn parse(line: &str) -> Result<EpdRecord, String>{
+        let mut rest = line.trim();
+        let mut fields = vec![];
+        // some files have the move counters of a full fen after the four fields instead of hmvc and fmvn
+        let counters = rest.split_whitespace().skip(4).take(2).filter(|field| field.parse::<u32>().is_ok()).count() == 2;
+        let field_count = if counters { 6 } else { 4 };
+        while fields.len() < field_count {
+            let (field, remaining) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            if field.is_empty() {
+                return Err(format!("expected four fen fields in {}", line));
+            }
+            fields.push(field);
+            rest = remaining.trim_start();
+        }
+        let mut operations = vec![];
+        let mut operation: Vec<String> = vec![];
+        for token in tokenize(rest)?{
+            match token {
+                Some(token) => operation.push(token),
+                None => {
+                    if !operation.is_empty(){
+                        let opcode = operation.remove(0);
+                        operations.push((opcode, operation));
+                    }
+                    operation = vec![];
+                }
+            }
+        }
+        if !operation.is_empty() {
+            return Err(format!("operation {} is not terminated by ;", operation.join(" ")));
+        }
+        Ok(EpdRecord { fen: fields.join(" "), operations })
+    }
+
+    pub fn operation(&self, opcode: &str) -> Option<&[String]>{
+        self.operations.iter().find(|(op, _)| op == opcode).map(|(_, operands)| operands.as_slice())
+    }
+
+    pub fn id(&self) -> Option<&str>{
+        self.operation("id").and_then(|operands| operands.first()).map(String::as_str)
+    }
+
+    // the move counters come from the hmvc and fmvn operations when the record has them
+    pub fn board(&self) -> Result<Board, FenError>{
+        if self.fen.split_whitespace().count() > 4 {
+            return Board::try_from_fen(&self.fen);
+        }
+        let counter = |opcode| self.operation(opcode).and_then(|operands| operands.first()).map(String::as_str);
+        Board::try_from_fen(&format!("{} {} {}", self.fen, counter("hmvc").unwrap_or("0"), counter("fmvn").unwrap_or("1")))
+    }
+
+    // the operands of an operation like bm or am as moves, in san or long algebraic notation
+    pub fn moves(&self, opcode: &str) -> Result<Vec<Move>, String>{
+        let mut board = self.board().map_err(|err| err.to_string())?;
+        self.operation(opcode).unwrap_or_default().iter()
+            .map(|operand| parse_move(&mut board, operand).ok_or(format!("illegal move {} in {}", operand, opcode)))
+            .collect()
+    }
+}
+
+pub fn parse_move(board: &mut Board, s: &str) -> Option<Move>{
+    board.move_from_san(s).or_else(|| board.move_from_long_algebraic_notation(s.into()))
+}
+
+// operands split on whitespace, quoted strings are one operand, None marks the ; ending an operation
+fn tokenize(s: &str) -> Result<Vec<Option<String>>, String>{
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => tokens.push(None),
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => string.extend(chars.next()),
+                        Some(c) => string.push(c),
+                        None => return Err(format!("unterminated string {}", string))
+                    }
+                }
+                tokens.push(Some(string));
+            },
+            c if c.is_whitespace() => {},
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ';') {
+                    word.push(c);
+                }
+                tokens.push(Some(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::EpdRecord;
+
+    #[test]
+    fn parse_record(){
+        let record = EpdRecord::parse(r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";"#).unwrap();
+        assert_eq!(record.fen, "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - -");
+        assert_eq!(record.id(), Some("WAC.001"));
+        let bm = record.moves("bm").unwrap();
+        assert_eq!(bm.len(), 1);
+        assert_eq!(bm[0].long_algebraic_notation(), "g3g6");
+        assert!(record.moves("am").unwrap().is_empty());
+    }
+
+    #[test]
+    fn quoted_operands_and_counters(){
+        let record = EpdRecord::parse(r#"1k6/8/8/8/8/8/8/K6R w - - 3 40 ;bm Rh8+ ; c0 "Rh8=10, Rb1+=3; a;b";"#).unwrap();
+        assert_eq!(record.fen, "1k6/8/8/8/8/8/8/K6R w - - 3 40");
+        assert_eq!(record.operation("c0").unwrap(), ["Rh8=10, Rb1+=3; a;b"]);
+        assert_eq!(record.board().unwrap().fullmove_number(), 40);
+
+        let record = EpdRecord::parse("1k6/8/8/8/8/8/8/K6R w - - hmvc 3; fmvn 40; am h1h2 Rh7;").unwrap();
+        assert_eq!(record.board().unwrap().halfmove_clock(), 3);
+        assert_eq!(record.moves("am").unwrap().len(), 2);
+        assert!(EpdRecord::parse("1k6/8/8/8/8/8/8/K6R w - - bm Rh8").is_err());
+        assert!(EpdRecord::parse("1k6/8/8/8/8/8/8/K6R w").is_err());
+    }
+}
@@ -5,14 +5,27 @@ use uci::uciio;
 mod board;
 mod constants;
 mod engine;
+mod epd;
 mod uci;
 mod movegeneration;
+mod pgn;
 mod utils;
 
 
 
 
 fn main() -> Result<(), Box<dyn error::Error>> {
+    // without arguments the engine talks uci on stdin and stdout
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "makebook") {
+        return Ok(engine::bookbuilder::run(&args[1..])?);
+    }
+    if args.first().is_some_and(|arg| arg == "perft") {
+        return Ok(movegeneration::perft::run(&args[1..])?);
+    }
+    if args.first().is_some_and(|arg| arg == "epd") {
+        return Ok(engine::testsuite::run(&args[1..])?);
+    }
     movegeneration::setup(); //set up magics, not needed but will speed up the first movegeneration a bit by not having to do it during the first search
     let (thread_in,rx) = uciio::new_uci_in_tread();
     let (thread_out, tx) = uciio::new_uci_out_tread();
@@ -1,6 +1,8 @@
 pub mod movegenerator;
 pub mod singlemove;
 pub mod moveorder;
+pub mod perft;
+pub mod see;
 mod magic;
 
 
@@ -1,17 +1,75 @@
 use crate::board::{color::Color, Board};
 
-use super::singlemove::{Move, MoveType};
+use super::{see, singlemove::{Move, MoveType}};
 
+const LMR_MIN_DEPTH: usize = 3;
+// moves searched at the node before the reductions start
+const LMR_MIN_MOVES: usize = 3;
+// history score that makes a quiet move one ply less reduced, at most two plies
+const LMR_HISTORY_DIVISOR: usize = 8_000;
+const LMR_TABLE_SIZE: usize = 64;
 
+// what the search knows about a move when deciding how much to reduce it
+pub struct ReductionContext{
+    pub depth: usize,
+    // number of moves searched at the node before this one
+    pub move_number: usize,
+    pub ply: usize,
+    // the side that made the move
+    pub white: bool,
+    pub pv_node: bool,
+    pub capture: bool,
+    pub in_check: bool,
+    pub gives_check: bool,
+    // the static evaluation is better than two plies ago
+    pub improving: bool
+}
 
 pub struct MoveOrder{
     killer_moves: [[u32; 2]; 64],
-    history: [[[usize; 64];64];2]
+    history: [[[usize; 64];64];2],
+    // base reduction by depth and move number
+    reductions: [[u8; LMR_TABLE_SIZE]; LMR_TABLE_SIZE]
 }
 
 impl MoveOrder {
     pub fn default() -> MoveOrder{
-        MoveOrder { killer_moves: [[0;2];64], history: [[[0;64];64];2] }
+        let mut reductions = [[0; LMR_TABLE_SIZE]; LMR_TABLE_SIZE];
+        for (depth, row) in reductions.iter_mut().enumerate().skip(1){
+            for (move_number, reduction) in row.iter_mut().enumerate().skip(1){
+                *reduction = (0.5 + (depth as f64).ln() * (move_number as f64).ln() / 2.25) as u8;
+            }
+        }
+        MoveOrder { killer_moves: [[0;2];64], history: [[[0;64];64];2], reductions }
+    }
+
+    fn is_killer(&self, mv: &Move, ply: usize) -> bool{
+        ply < 64 && (self.killer_moves[ply][0] == mv.get_hash() || self.killer_moves[ply][1] == mv.get_hash())
+    }
+
+    fn history_score(&self, mv: &Move, white: bool) -> usize{
+        let i = if white { 0 } else { 1 };
+        self.history[i][mv.from() as usize][mv.to() as usize]
+    }
+
+    // plies a late quiet move is searched shallower, a reduced move that fails high is searched again at full depth
+    pub fn late_move_reduction(&self, mv: &Move, context: ReductionContext) -> usize{
+        if context.depth < LMR_MIN_DEPTH || context.move_number < LMR_MIN_MOVES || context.in_check || context.capture || mv.move_type().is_promotion(){
+            return 0;
+        }
+        let mut reduction = self.reductions[context.depth.min(LMR_TABLE_SIZE - 1)][context.move_number.min(LMR_TABLE_SIZE - 1)] as i32;
+        if context.pv_node{
+            reduction -= 1;
+        }
+        if context.gives_check || self.is_killer(mv, context.ply){
+            reduction -= 1;
+        }
+        if !context.improving{
+            reduction += 1;
+        }
+        reduction -= (self.history_score(mv, context.white) / LMR_HISTORY_DIVISOR).min(2) as i32;
+        // at least one ply is left to search
+        reduction.clamp(0, context.depth as i32 - 2) as usize
     }
 
     pub fn add_killer(&mut self, mv: &Move, ply: usize){
@@ -33,6 +91,10 @@ impl MoveOrder {
         moves.sort_by_cached_key(|mv| {
             let i = if board.get_turn() == Color::White { 0 } else { 1 };
             let mut weight = 1;
+            // captures that lose material come after the quiet moves
+            if (board.get_pice_pos(mv.to()).is_some() || mv.move_type() == MoveType::Pessant) && see::see(board, mv) < 0 {
+                return 0;
+            }
             if let Some(captured) = board.get_pice_pos(mv.to()) {
                 if let Some(pice) = board.get_pice_pos(mv.from()) {
                     weight += ((captured.pice_type() as usize) * 100 - pice.pice_type() as usize) * 1_000_000
@@ -41,7 +103,7 @@ impl MoveOrder {
                 if let Some(pice) = board.get_pice_pos(mv.from()) {
                     weight += (100 - pice.pice_type() as usize) * 1_000_000
                 }              
-            }else if ply < 64 && (self.killer_moves[ply][0] == mv.get_hash() || self.killer_moves[ply][1] == mv.get_hash()){
+            }else if self.is_killer(mv, ply){
                 weight += 100_000;
             }
             weight += self.history[i][mv.from() as usize][mv.to() as usize];
@@ -73,7 +135,41 @@ impl MoveOrder {
     }
 }
 
-// TODO check if move results in check
-pub fn late_move_reduction(ply_remaining: usize, extentions: usize, check: bool, mv: &Move, nbr_evaluated_moves: usize) -> bool{
-    ply_remaining >= 3 && extentions == 0 && nbr_evaluated_moves >= 3 && !check && !mv.move_type().is_promotion()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests{
+    use crate::movegeneration::singlemove::{Move, MoveType};
+
+    use super::{MoveOrder, ReductionContext};
+
+    fn context(depth: usize, move_number: usize) -> ReductionContext{
+        ReductionContext { depth, move_number, ply: 3, white: true, pv_node: false, capture: false, in_check: false, gives_check: false, improving: true }
+    }
+
+    #[test]
+    fn late_move_reductions(){
+        let mut move_order = MoveOrder::default();
+        let mv = Move::new(6, 21, MoveType::Normal);
+        let reduction = |move_order: &MoveOrder, context| move_order.late_move_reduction(&mv, context);
+
+        // early moves, shallow nodes and tactical moves are searched at full depth
+        assert_eq!(reduction(&move_order, context(8, 2)), 0);
+        assert_eq!(reduction(&move_order, context(2, 30)), 0);
+        assert_eq!(reduction(&move_order, ReductionContext { capture: true, ..context(8, 30) }), 0);
+        assert_eq!(reduction(&move_order, ReductionContext { in_check: true, ..context(8, 30) }), 0);
+
+        // later moves and deeper nodes are reduced more, but a ply is always left
+        let base = reduction(&move_order, context(8, 20));
+        assert!(base > reduction(&move_order, context(8, 4)));
+        assert!(reduction(&move_order, context(30, 40)) > base);
+        assert!(reduction(&move_order, ReductionContext { improving: false, ..context(3, 60) }) <= 1);
+
+        assert!(reduction(&move_order, ReductionContext { improving: false, ..context(8, 20) }) > base);
+        assert!(reduction(&move_order, ReductionContext { pv_node: true, ..context(8, 20) }) < base);
+        assert!(reduction(&move_order, ReductionContext { gives_check: true, ..context(8, 20) }) < base);
+        move_order.add_killer(&mv, 3);
+        assert!(reduction(&move_order, context(8, 20)) < base);
+        move_order.clear();
+        move_order.add_history(&mv, 20_000, true);
+        assert!(reduction(&move_order, context(8, 20)) < base);
+    }
+}
@@ -0,0 +1,176 @@
+use std::{collections::HashMap, fs, time::{Duration, Instant}};
+
+use crate::board::Board;
+
+use super::singlemove::Move;
+
+// number of leaf nodes at the given depth, the last ply is counted without making the moves
+pub fn perft(board: &mut Board, depth: u8) -> u64{
+    if depth == 0{
+        return 1;
+    }
+    let moves = board.get_possible_moves_turn();
+    if depth == 1{
+        return moves.len() as u64;
+    }
+    let mut res = 0;
+    for mv in moves{
+        board.make_move(mv);
+        res += perft(board, depth - 1);
+        board.undo_last_move();
+    }
+    res
+}
+
+// perft that remembers the count of every (position, depth) it has seen, transpositions are only counted once
+pub fn perft_hash(board: &mut Board, table: &mut HashMap<(u64, u8), u64>, depth: u8) -> u64{
+    if depth <= 1{
+        return perft(board, depth);
+    }
+    let key = (board.get_zobrist_hash(), depth);
+    if let Some(nodes) = table.get(&key) {
+        return *nodes;
+    }
+    let mut res = 0;
+    for mv in board.get_possible_moves_turn(){
+        board.make_move(mv);
+        res += perft_hash(board, table, depth - 1);
+        board.undo_last_move();
+    }
+    table.insert(key, res);
+    res
+}
+
+// perft split up by the first move
+#[derive(Debug, Clone)]
+pub struct Divide{
+    pub moves: Vec<(Move, u64)>,
+    pub nodes: u64,
+    pub time: Duration
+}
+
+impl Divide {
+    pub fn nps(&self) -> u64{
+        (self.nodes as f64 / self.time.as_secs_f64().max(1e-6)) as u64
+    }
+}
+
+pub fn divide(board: &mut Board, depth: u8, hashed: bool) -> Divide{
+    let start = Instant::now();
+    let mut table = HashMap::new();
+    let mut moves = vec![];
+    if depth > 0{
+        for mv in board.get_possible_moves_turn(){
+            board.make_move(mv);
+            let nodes = if hashed { perft_hash(board, &mut table, depth - 1) } else { perft(board, depth - 1) };
+            board.undo_last_move();
+            moves.push((mv, nodes));
+        }
+    }
+    let nodes = if depth == 0 { 1 } else { moves.iter().map(|(_, nodes)| nodes).sum() };
+    Divide { moves, nodes, time: start.elapsed() }
+}
+
+// a line of a perft suite: fen ;D1 20 ;D2 400 ...
+struct SuiteEntry{
+    fen: String,
+    counts: Vec<(u8, u64)>
+}
+
+fn parse_suite_line(line: &str) -> Result<SuiteEntry, String>{
+    let mut fields = line.split(';');
+    let fen = fields.next().unwrap_or_default().trim().to_string();
+    let mut counts = vec![];
+    for field in fields{
+        let invalid = || format!("invalid perft count {}", field.trim());
+        let (depth, nodes) = field.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+        let depth = depth.strip_prefix('D').and_then(|depth| depth.parse().ok()).ok_or_else(invalid)?;
+        let nodes = nodes.trim().parse().map_err(|_| invalid())?;
+        counts.push((depth, nodes));
+    }
+    Ok(SuiteEntry { fen, counts })
+}
+
+// perft <suite.epd> [--depth N] [--hash], checks every count up to the depth and lists the mismatches
+pub fn run(args: &[String]) -> Result<(), String>{
+    let usage = "usage: perft <suite.epd> [--depth N] [--hash]";
+    let [input, flags @ ..] = args else { return Err(usage.into()) };
+    let mut max_depth = u8::MAX;
+    let mut hashed = false;
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--depth" => {
+                let value = flags.next().ok_or(usage)?;
+                max_depth = value.parse().map_err(|_| format!("invalid value for --depth: {}", value))?;
+            },
+            "--hash" => hashed = true,
+            _ => return Err(usage.into())
+        }
+    }
+    let suite = fs::read_to_string(input).map_err(|err| format!("could not read {}: {}", input, err))?;
+    let start = Instant::now();
+    let (mut checked, mut nodes) = (0, 0);
+    let mut mismatches = vec![];
+    for (idx, line) in suite.lines().enumerate().filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#')){
+        let entry = parse_suite_line(line).map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        let mut board = Board::try_from_fen(&entry.fen).map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        println!("{}", entry.fen);
+        for &(depth, expected) in entry.counts.iter().filter(|(depth, _)| *depth <= max_depth){
+            let result = divide(&mut board, depth, hashed);
+            nodes += result.nodes;
+            checked += 1;
+            if result.nodes == expected {
+                println!("  depth {} {} ok", depth, result.nodes);
+            }else {
+                println!("  depth {} {} MISMATCH expected {}", depth, result.nodes, expected);
+                mismatches.push(format!("line {} depth {}: {} expected {}", idx + 1, depth, result.nodes, expected));
+            }
+        }
+    }
+    let time = start.elapsed();
+    println!("{} of {} counts correct, {} nodes in {} ms ({} nps)",
+        checked - mismatches.len(), checked, nodes, time.as_millis(), (nodes as f64 / time.as_secs_f64().max(1e-6)) as u64);
+    if mismatches.is_empty() {
+        Ok(())
+    }else {
+        Err(format!("{} mismatches\n{}", mismatches.len(), mismatches.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::collections::HashMap;
+
+    use crate::board::Board;
+
+    use super::{divide, parse_suite_line, perft, perft_hash};
+
+    #[test]
+    fn divide_kiwipete(){
+        let mut board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
+        let result = divide(&mut board, 2, false);
+        assert_eq!(result.moves.len(), 48);
+        assert_eq!(result.nodes, 2_039);
+        let castle = result.moves.iter().find(|(mv, _)| mv.long_algebraic_notation() == "e1g1").unwrap();
+        assert_eq!(castle.1, 43);
+        assert_eq!(divide(&mut board, 2, true).nodes, 2_039);
+        assert_eq!(divide(&mut board, 0, false).nodes, 1);
+    }
+
+    #[test]
+    fn hashed_perft_matches(){
+        let mut board = Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - ");
+        let mut table = HashMap::new();
+        assert_eq!(perft_hash(&mut board, &mut table, 4), 43_238);
+        assert_eq!(perft(&mut board, 4), 43_238);
+    }
+
+    #[test]
+    fn suite_line(){
+        let entry = parse_suite_line("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400").unwrap();
+        assert_eq!(entry.fen, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(entry.counts, vec![(1, 20), (2, 400)]);
+        assert!(parse_suite_line("8/8/8/8/8/8/8/8 w - - ;D1 x").is_err());
+    }
+}
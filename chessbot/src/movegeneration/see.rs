@@ -0,0 +1,148 @@
+use crate::{board::{color::Color, pice::PiceType, state::PiceBoards, Board}, constants};
+
+use super::{magic, singlemove::{Move, MoveType}};
+
+// pawn, knight, bishop, rook, queen, king indexed by PiceType - 1
+const SEE_VALUE: [i32; 6] = [100, 320, 330, 500, 900, 20_000];
+
+fn value(pice_type: PiceType) -> i32{
+    SEE_VALUE[pice_type as usize - 1]
+}
+
+fn promotion_value(move_type: MoveType) -> Option<i32>{
+    match move_type {
+        MoveType::PromotionQueen => Some(value(PiceType::Queen)),
+        MoveType::PromotionRook => Some(value(PiceType::Rook)),
+        MoveType::PromotionBishop => Some(value(PiceType::Bishop)),
+        MoveType::PromotionKnight => Some(value(PiceType::Knight)),
+        _ => None
+    }
+}
+
+// squares a pawn of the color attacks the square from, a white pawn stands where a black pawn on the square would capture
+fn pawn_attackers(square: usize, color: Color) -> u64{
+    match color {
+        Color::White if square >= 56 => constants::BLACK_PAWN_CAPTURES[square - 8] << 8,
+        Color::White => constants::BLACK_PAWN_CAPTURES[square],
+        Color::Black if square >= 56 => 0,
+        Color::Black => constants::WHITE_PAWN_CAPTURES[square]
+    }
+}
+
+// every pice of the color attacking the square through the occupied squares
+fn attackers(pices: &PiceBoards, color: Color, square: usize, occupied: u64) -> u64{
+    let attackers = (pawn_attackers(square, color) & pices.pawns)
+        | (constants::HORSE_BIT_MOVES[square] & pices.knights)
+        | (constants::KINGS_BIT_MOVES[square] & pices.king)
+        | (magic::get_diagonal_moves(square, occupied) & pices.diagonal_sliders)
+        | (magic::get_orthogonal_moves(square, occupied) & pices.orthoganal_sliders);
+    attackers & occupied
+}
+
+// the cheapest of the attackers, queens are in both slider boards
+fn least_valuable(pices: &PiceBoards, attackers: u64) -> Option<(u64, PiceType)>{
+    let queens = pices.diagonal_sliders & pices.orthoganal_sliders;
+    [
+        (pices.pawns, PiceType::Pawn),
+        (pices.knights, PiceType::Knight),
+        (pices.diagonal_sliders & !queens, PiceType::Bishop),
+        (pices.orthoganal_sliders & !queens, PiceType::Rook),
+        (queens, PiceType::Queen),
+        (pices.king, PiceType::King)
+    ].into_iter()
+        .find(|(board, _)| board & attackers != 0)
+        .map(|(board, pice_type)| (1 << (board & attackers).trailing_zeros(), pice_type))
+}
+
+// static exchange evaluation, the material the side to move wins with the move when both sides keep capturing on
+// the target square with their cheapest attacker and either may stop when that is better. removing a capturer
+// opens the line for sliders behind it, pins are ignored
+pub fn see(board: &Board, mv: &Move) -> i32{
+    let (from, to) = (mv.from() as usize, mv.to() as usize);
+    let Some(attacker) = board.get_pice_pos(mv.from()) else { return 0 };
+    let mut occupied = board.state.white.bitmap_all() | board.state.black.bitmap_all();
+    let mut gain = [0; 32];
+    gain[0] = match board.get_pice_pos(mv.to()) {
+        Some(captured) => value(captured.pice_type()),
+        None if mv.move_type() == MoveType::Pessant => {
+            // the captured pawn stands behind the target square
+            let captured = if attacker.color() == Color::White { to - 8 } else { to + 8 };
+            occupied &= !(1 << captured);
+            value(PiceType::Pawn)
+        },
+        None => 0
+    };
+    let mut on_square = value(attacker.pice_type());
+    if let Some(promotion) = promotion_value(mv.move_type()) {
+        gain[0] += promotion - value(PiceType::Pawn);
+        on_square = promotion;
+    }
+    occupied &= !(1 << from);
+
+    let mut color = attacker.color().other();
+    let mut depth = 0;
+    while depth + 1 < gain.len() {
+        let pices = board.state.piceboards(color);
+        let Some((square, pice_type)) = least_valuable(pices, attackers(pices, color, to, occupied)) else { break };
+        // the king can only take last, when the square is no longer defended
+        if pice_type == PiceType::King && attackers(board.state.piceboards(color.other()), color.other(), to, occupied & !square) != 0 {
+            break;
+        }
+        depth += 1;
+        gain[depth] = on_square - gain[depth - 1];
+        on_square = value(pice_type);
+        occupied &= !square;
+        color = color.other();
+    }
+    while depth > 0 {
+        gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        depth -= 1;
+    }
+    gain[0]
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::board::Board;
+
+    use super::see;
+
+    fn see_of(fen: &str, mv: &str) -> i32{
+        let mut board = Board::from_fen(fen);
+        let mv = board.move_from_long_algebraic_notation(mv.into()).unwrap();
+        see(&board, &mv)
+    }
+
+    #[test]
+    fn simple_exchanges(){
+        // undefended pawn
+        assert_eq!(see_of("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+        // the long exchange on e5 costs the knight for a pawn
+        assert_eq!(see_of("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"), -220);
+        // quiet move to an attacked square
+        assert_eq!(see_of("4k3/8/8/4p3/8/8/8/3RK3 w - - 0 1", "d1d4"), -500);
+        assert_eq!(see_of("4k3/8/8/8/8/8/8/3RK3 w - - 0 1", "d1d4"), 0);
+    }
+
+    #[test]
+    fn x_rays(){
+        // the queen behind the rook recaptures, the defended pawn is won
+        assert_eq!(see_of("4k3/8/3r4/3p4/8/8/3R4/3QK3 w - - 0 1", "d2d5"), 100);
+        // without the queen the rook is traded for a pawn
+        assert_eq!(see_of("4k3/8/3r4/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5"), -400);
+        // a bishop behind a pawn
+        assert_eq!(see_of("4k3/8/4n3/3P4/8/1B6/8/4K3 w - - 0 1", "d5e6"), 320);
+        assert_eq!(see_of("4k3/5p2/4n3/3P4/8/1B6/8/4K3 w - - 0 1", "d5e6"), 320);
+    }
+
+    #[test]
+    fn king_and_special_moves(){
+        // the king can only recapture when the bishop doesn't defend the square
+        assert_eq!(see_of("8/8/4k3/4p3/8/8/1B6/K3R3 w - - 0 1", "e1e5"), 100);
+        assert_eq!(see_of("8/8/4k3/4p3/8/8/8/K3R3 w - - 0 1", "e1e5"), -400);
+        // en passant and promotion
+        assert_eq!(see_of("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2", "e5d6"), 100);
+        assert_eq!(see_of("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800);
+        assert_eq!(see_of("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8q"), 1300);
+    }
+}
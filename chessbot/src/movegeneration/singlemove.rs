@@ -35,6 +35,11 @@ impl Move {
         Move {value: (to as u32)  | (from as u32) << 6 | (move_type as u32) << 16, captured: None}
     }
 
+    // inverse of get_hash, the captured pice is not part of the hash
+    pub fn from_hash(value: u32) -> Move{
+        Move { value, captured: None }
+    }
+
     pub fn null_move() -> Move{
         Move { value: 0, captured: None }
     }
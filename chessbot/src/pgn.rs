@@ -0,0 +1,414 @@
+use crate::{board::{fen::FenError, Board}, movegeneration::singlemove::Move};
+
+// https://www.saremo.net/pgn-standard (the pgn standard), export format lines are at most 80 characters
+const MAX_LINE_LENGTH: usize = 80;
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"), ("Site", "?"), ("Date", "????.??.??"), ("Round", "?"), ("White", "?"), ("Black", "?"), ("Result", "*")
+];
+// move suffix annotations and the numeric annotation glyphs they stand for
+const SUFFIXES: [(&str, u8); 6] = [("!!", 3), ("??", 4), ("!?", 5), ("?!", 6), ("!", 1), ("?", 2)];
+
+#[derive(Debug, Clone)]
+pub struct PgnMove{
+    pub mv: Move,
+    pub nags: Vec<u8>,
+    pub comment: Option<String>,
+    // alternatives to this move, played from the position before it
+    pub variations: Vec<Vec<PgnMove>>
+}
+
+impl PgnMove {
+    pub fn new(mv: Move) -> PgnMove{
+        PgnMove { mv, nags: vec![], comment: None, variations: vec![] }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PgnGame{
+    pub tags: Vec<(String, String)>,
+    // a comment before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<PgnMove>,
+    pub result: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token{
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    VariationStart,
+    VariationEnd,
+    San(String),
+    Result(String)
+}
+
+impl PgnGame {
+    pub fn new() -> PgnGame{
+        PgnGame { tags: vec![], comment: None, moves: vec![], result: "*".into() }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str>{
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str){
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, current)) => *current = value.into(),
+            None => self.tags.push((name.into(), value.into()))
+        }
+    }
+
+    // the position the game starts from, set with the FEN tag
+    pub fn start_position(&self) -> Result<Board, FenError>{
+        self.tag("FEN").map_or(Ok(Board::default()), Board::try_from_fen)
+    }
+
+    // the main line played out on a board
+    #[allow(dead_code)]
+    pub fn board(&self) -> Result<Board, FenError>{
+        let mut board = self.start_position()?;
+        for pgn_move in &self.moves{
+            board.make_move(pgn_move.mv);
+        }
+        Ok(board)
+    }
+
+    // the game in pgn export format, the seven tag roster comes first
+    #[allow(dead_code)]
+    pub fn to_pgn(&self) -> String{
+        let mut res = String::new();
+        for (name, default) in SEVEN_TAG_ROSTER{
+            let value = if name == "Result" { Some(self.result.as_str()) } else { self.tag(name) };
+            res.push_str(&format!("[{} \"{}\"]\n", name, escape(value.unwrap_or(default))));
+        }
+        for (name, value) in self.tags.iter().filter(|(name, _)| !SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name)){
+            res.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+        }
+        res.push('\n');
+
+        let mut words = vec![];
+        if let Some(comment) = &self.comment{
+            words.push(format!("{{{}}}", comment));
+        }
+        // games that were read have a valid FEN tag, the moves of any other game were made from the start
+        write_line(&mut self.start_position().unwrap_or_else(|_| Board::default()), &self.moves, &mut words);
+        words.push(self.result.clone());
+
+        let mut line = String::new();
+        for word in words{
+            if !line.is_empty() && line.len() + 1 + word.len() > MAX_LINE_LENGTH{
+                res.push_str(&line);
+                res.push('\n');
+                line.clear();
+            }
+            if !line.is_empty(){
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        res.push_str(&line);
+        res.push('\n');
+        res
+    }
+}
+
+fn escape(value: &str) -> String{
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// movetext for a line, the board is back at the starting position afterwards
+fn write_line(board: &mut Board, moves: &[PgnMove], words: &mut Vec<String>){
+    // black moves need their number at the start of a line and after comments or variations
+    let mut needs_number = true;
+    for pgn_move in moves{
+        if board.is_white_move(){
+            words.push(format!("{}.", board.fullmove_number()));
+        }else if needs_number{
+            words.push(format!("{}...", board.fullmove_number()));
+        }
+        words.push(board.san(pgn_move.mv));
+        words.extend(pgn_move.nags.iter().map(|nag| format!("${}", nag)));
+        needs_number = false;
+        for variation in &pgn_move.variations{
+            let mut variation_words = vec![];
+            write_line(board, variation, &mut variation_words);
+            if !variation_words.is_empty(){
+                let last = variation_words.len() - 1;
+                variation_words[0].insert(0, '(');
+                variation_words[last].push(')');
+                words.append(&mut variation_words);
+                needs_number = true;
+            }
+        }
+        if let Some(comment) = &pgn_move.comment{
+            words.push(format!("{{{}}}", comment));
+            needs_number = true;
+        }
+        board.make_move(pgn_move.mv);
+    }
+    moves.iter().for_each(|_| board.undo_last_move());
+}
+
+fn is_result(word: &str) -> bool{
+    matches!(word, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+fn tokenize(pgn: &str) -> Vec<Token>{
+    let mut tokens = vec![];
+    let mut comment: Option<String> = None;
+    for line in pgn.lines(){
+        let trimmed = line.trim();
+        if comment.is_none() && trimmed.starts_with('['){
+            let inner = trimmed.trim_start_matches('[').trim_end_matches(']');
+            let (name, value) = inner.split_once(' ').unwrap_or((inner, ""));
+            let value = value.trim();
+            let value = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')).unwrap_or(value).replace("\\\"", "\"").replace("\\\\", "\\");
+            tokens.push(Token::Tag(name.into(), value));
+            continue;
+        }
+        // lines starting with % are ignored
+        if comment.is_none() && trimmed.starts_with('%'){
+            continue;
+        }
+        let mut word = String::new();
+        for (pos, c) in trimmed.char_indices().chain([(trimmed.len(), ' ')]){
+            if let Some(text) = comment.as_mut(){
+                if c == '}'{
+                    tokens.push(Token::Comment(text.trim().into()));
+                    comment = None;
+                }else {
+                    text.push(c);
+                }
+                continue;
+            }
+            if !c.is_whitespace() && !"{;()".contains(c){
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty(){
+                tokens.extend(word_tokens(&std::mem::take(&mut word)));
+            }
+            match c {
+                '{' => comment = Some(String::new()),
+                ';' => {
+                    tokens.push(Token::Comment(trimmed[pos + 1..].trim().into()));
+                    break;
+                },
+                '(' => tokens.push(Token::VariationStart),
+                ')' => tokens.push(Token::VariationEnd),
+                _ => {}
+            }
+        }
+    }
+    tokens
+}
+
+fn word_tokens(word: &str) -> Vec<Token>{
+    if is_result(word){
+        return vec![Token::Result(word.into())];
+    }
+    if let Some(nag) = word.strip_prefix('$'){
+        return nag.parse().map(|nag| vec![Token::Nag(nag)]).unwrap_or_default();
+    }
+    // move numbers can be written together with the move like 1.e4 or 3...Nf6, only digits followed by dots are
+    // a number so castling written with zeros like 0-0 stays a move
+    let without_number = word.trim_start_matches(|c: char| c.is_ascii_digit());
+    let san = match without_number.strip_prefix('.') {
+        Some(rest) => rest.trim_start_matches('.'),
+        None if without_number.is_empty() => "",
+        None => word
+    };
+    if san.is_empty(){
+        return vec![];
+    }
+    let stripped = san.trim_end_matches(['!', '?']);
+    let mut tokens = vec![Token::San(stripped.into())];
+    let suffix = &san[stripped.len()..];
+    if let Some((_, nag)) = SUFFIXES.iter().find(|(text, _)| *text == suffix){
+        tokens.push(Token::Nag(*nag));
+    }
+    tokens
+}
+
+// reads a line of moves until the end of the variation or game, the board is back at the starting position afterwards
+fn parse_line(tokens: &[Token], idx: &mut usize, board: &mut Board) -> Result<Vec<PgnMove>, String>{
+    let mut moves: Vec<PgnMove> = vec![];
+    while let Some(token) = tokens.get(*idx){
+        match token {
+            Token::San(san) => {
+                let mv = board.move_from_san(san).ok_or(format!("illegal move {} after {}", san, describe(&moves)))?;
+                board.make_move(mv);
+                moves.push(PgnMove::new(mv));
+            },
+            Token::Nag(nag) => {
+                if let Some(last) = moves.last_mut(){
+                    last.nags.push(*nag);
+                }
+            },
+            Token::Comment(text) => {
+                if let Some(last) = moves.last_mut(){
+                    last.comment = Some(match last.comment.take() {
+                        Some(previous) => format!("{} {}", previous, text),
+                        None => text.clone()
+                    });
+                }
+            },
+            Token::VariationStart => {
+                let last = moves.last().ok_or("variation before the first move")?.mv;
+                *idx += 1;
+                board.undo_last_move();
+                let variation = parse_line(tokens, idx, board);
+                board.make_move(last);
+                let variation = variation?;
+                if !matches!(tokens.get(*idx), Some(Token::VariationEnd)){
+                    return Err("unterminated variation".into());
+                }
+                moves.last_mut().unwrap().variations.push(variation);
+            },
+            Token::VariationEnd | Token::Result(_) | Token::Tag(..) => break
+        }
+        *idx += 1;
+    }
+    moves.iter().for_each(|_| board.undo_last_move());
+    Ok(moves)
+}
+
+fn describe(moves: &[PgnMove]) -> String{
+    match moves.last() {
+        Some(pgn_move) => pgn_move.mv.long_algebraic_notation(),
+        None => "the start".into()
+    }
+}
+
+fn parse_game(tokens: &[Token]) -> Result<PgnGame, String>{
+    let mut game = PgnGame::new();
+    let mut idx = 0;
+    while let Some(Token::Tag(name, value)) = tokens.get(idx){
+        game.set_tag(name, value);
+        idx += 1;
+    }
+    if let Some(Token::Comment(text)) = tokens.get(idx){
+        game.comment = Some(text.clone());
+        idx += 1;
+    }
+    let mut board = game.start_position().map_err(|err| format!("invalid FEN tag: {}", err))?;
+    game.moves = parse_line(tokens, &mut idx, &mut board)?;
+    match tokens.get(idx) {
+        Some(Token::Result(result)) => game.result = result.clone(),
+        Some(Token::VariationEnd) => return Err("unexpected )".into()),
+        _ => game.result = game.tag("Result").unwrap_or("*").into()
+    }
+    Ok(game)
+}
+
+// every game in a pgn file, a game that can't be read gives an error without stopping the others
+pub fn read_games(pgn: &str) -> Vec<Result<PgnGame, String>>{
+    let tokens = tokenize(pgn);
+    let mut games = vec![];
+    let mut start = 0;
+    for (idx, token) in tokens.iter().enumerate(){
+        // a game ends with its result or when the tags of the next game start
+        let end = match token {
+            Token::Result(_) => Some(idx + 1),
+            Token::Tag(..) if idx > start && !matches!(tokens[idx - 1], Token::Tag(..)) => Some(idx),
+            _ => None
+        };
+        if let Some(end) = end{
+            games.push(parse_game(&tokens[start..end]));
+            start = end;
+        }
+    }
+    if tokens[start..].iter().any(|token| matches!(token, Token::San(_))){
+        games.push(parse_game(&tokens[start..]));
+    }
+    games
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{read_games, PgnGame, PgnMove};
+    use crate::board::Board;
+
+    const GAME: &str = r#"[Event "Casual \"blitz\""]
+[Site "?"]
+[White "Anderssen"]
+[Black "Kieseritzky"]
+[Result "1-0"]
+[Opening "Kings Gambit"]
+
+{Before the game} 1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 b5?! 5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5
+8. Nh4 $1 Qg5 (8... g6 9. Nf5 {and white is better}) 9. Nf5 c6 10. g4 Nf6 11. Rg1 cxb5
+12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8 15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6 Bxg1
+19. e5 Qxa1+ 20. Ke2 Na6 21. Nxg7+ Kd8 22. Qf6+ Nxf6 23. Be7# 1-0
+"#;
+
+    #[test]
+    fn read_game(){
+        let games = read_games(GAME);
+        assert_eq!(games.len(), 1);
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(game.tag("White"), Some("Anderssen"));
+        assert_eq!(game.tag("Event"), Some("Casual \"blitz\""));
+        assert_eq!(game.comment.as_deref(), Some("Before the game"));
+        assert_eq!(game.moves.len(), 45);
+        assert_eq!(game.result, "1-0");
+        assert_eq!(game.moves[7].nags, vec![6]);
+        assert_eq!(game.moves[14].nags, vec![1]);
+        let variation = &game.moves[15].variations[0];
+        assert_eq!(variation.len(), 2);
+        assert_eq!(variation[1].comment.as_deref(), Some("and white is better"));
+        let mut board = game.board().unwrap();
+        assert!(board.get_possible_moves_turn().is_empty() && board.in_check());
+    }
+
+    #[test]
+    fn write_and_read_back(){
+        let game = read_games(GAME).remove(0).unwrap();
+        let pgn = game.to_pgn();
+        assert!(pgn.starts_with("[Event \"Casual \\\"blitz\\\"\"]\n[Site \"?\"]\n[Date \"????.??.??\"]"));
+        assert!(pgn.contains("[Opening \"Kings Gambit\"]\n\n{Before the game} 1. e4 e5"));
+        assert!(pgn.contains("4. Kf1 b5 $6 5. Bxb5"));
+        assert!(pgn.contains("8. Nh4 $1 Qg5 (8... g6 9. Nf5 {and white is better}) 9. Nf5"));
+        assert!(pgn.contains("23. Be7# 1-0"));
+        assert!(pgn.lines().all(|line| line.len() <= 80));
+        assert_eq!(read_games(&pgn).remove(0).unwrap().to_pgn(), pgn);
+    }
+
+    #[test]
+    fn castling_with_zeros(){
+        let game = read_games("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 *").remove(0).unwrap();
+        assert_eq!(game.moves.len(), 7);
+        assert_eq!(game.moves[6].mv.long_algebraic_notation(), "e1g1");
+        let game = read_games("1.d4 d5 2.Nc3 Nc6 3.Bf4 Bf5 4.Qd2 Qd7 5.0-0-0 0-0-0 *").remove(0).unwrap();
+        assert_eq!(game.moves[9].mv.long_algebraic_notation(), "e8c8");
+    }
+
+    #[test]
+    fn several_games_and_errors(){
+        let pgn = "[Event \"a\"]\n\n1. e4 e5 *\n\n[Event \"b\"]\n\n1. e4 Ke7 2. Ke2 *\n\n[Event \"c\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 1\"]\n\n1. O-O Kd7 (1... Ke7) 1/2-1/2\n[Event \"d\"]\n\n1. d4\n\n[Event \"e\"]\n[FEN \"garbage\"]\n\n1. e4 *";
+        let games = read_games(pgn);
+        assert_eq!(games.len(), 5);
+        assert_eq!(games[0].as_ref().unwrap().moves.len(), 2);
+        assert!(games[1].is_err());
+        let game = games[2].as_ref().unwrap();
+        assert_eq!(game.result, "1/2-1/2");
+        assert_eq!(game.moves[1].variations.len(), 1);
+        assert!(game.to_pgn().contains("1. O-O Kd7 (1... Ke7) 1/2-1/2"));
+        assert_eq!(games[3].as_ref().unwrap().result, "*");
+        assert!(games[4].as_ref().unwrap_err().contains("invalid FEN tag"));
+    }
+
+    #[test]
+    fn write_new_game(){
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R b K - 0 12");
+        let mut game = PgnGame::new();
+        game.set_tag("FEN", "4k3/8/8/8/8/8/8/4K2R b K - 0 12");
+        for mv in ["e8d7", "e1g1"]{
+            let mv = board.move_from_long_algebraic_notation(mv.into()).unwrap();
+            board.make_move(mv);
+            game.moves.push(PgnMove::new(mv));
+        }
+        assert!(game.to_pgn().ends_with("\n\n12... Kd7 13. O-O *\n"));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod uci_message;
+pub mod uci_option;
 pub mod uciio;
\ No newline at end of file
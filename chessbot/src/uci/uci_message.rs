@@ -1,4 +1,6 @@
-use crate::{board::Board, movegeneration::singlemove::{MoveType,Move}};
+use std::fmt;
+
+use crate::{board::{fen::FenError, Board}, movegeneration::{perft::Divide, singlemove::Move}, uci::uci_option::UciOption};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Score{
@@ -14,6 +16,33 @@ impl Score {
     }
 }
 
+const COMMANDS: [&str; 12] = ["uci", "debug", "isready", "setoption", "register", "ucinewgame", "position", "go", "stop", "ponderhit", "quit", "perft"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UciParseError{
+    Empty,
+    UnknownCommand(String),
+    MissingValue(&'static str),
+    InvalidValue(&'static str, String),
+    InvalidFen(FenError),
+    IllegalMove(String)
+}
+
+impl fmt::Display for UciParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciParseError::Empty => write!(f, "empty command"),
+            UciParseError::UnknownCommand(command) => write!(f, "unknown command {}", command),
+            UciParseError::MissingValue(name) => write!(f, "missing {}", name),
+            UciParseError::InvalidValue(name, value) => write!(f, "invalid value {} for {}", value, name),
+            UciParseError::InvalidFen(err) => write!(f, "invalid fen: {}", err),
+            UciParseError::IllegalMove(mv) => write!(f, "illegal move {}", mv),
+        }
+    }
+}
+
+impl std::error::Error for UciParseError {}
+
 #[derive(Clone)]
 #[allow(dead_code)]
 pub enum UciMessage {
@@ -41,6 +70,7 @@ pub enum UciMessage {
         btime: Option<u64>,
         winc: Option<u64>,
         binc: Option<u64>,
+        moves_to_go: Option<u64>,
         depth: Option<u8>,
         nodes: Option<u64>,
         mate: Option<u8>,
@@ -50,6 +80,11 @@ pub enum UciMessage {
     Stop,
     Ponderhit,
     Quit,
+    // not part of uci, perft N or go perft N counts the leaf nodes split up by the first move
+    Perft{
+        depth: u8,
+        hashed: bool
+    },
 
     // Engine to GUI
 
@@ -71,7 +106,7 @@ pub enum UciMessage {
         time: Option<u64>,
         nodes: Option<u64>,
         pv: Option<Vec<Move>>,
-        multipv: Option<Vec<Vec<Move>>>,
+        multipv: Option<u16>,
         score: Option<Score>,
         currmove: Option<Move>,
         currmovenumber: Option<u16>,
@@ -84,111 +119,41 @@ pub enum UciMessage {
         refutation: Option<Vec<Move>>,
         currline: Option<(Option<u16>, Vec<Move>)>
     },
-    Option,
+    Option(UciOption),
+    Divide(Divide),
     Unknown(String),
 }
 
 impl UciMessage {
-    pub fn parse(s: String) -> UciMessage{
-        if s == "uci"{
-            UciMessage::Uci
-        } else if s.starts_with("debug") {
-            if s.contains("on"){
-                UciMessage::Debug(true)
-            }else {
-                UciMessage::Debug(false)
-            }
-            
-        } else if s == "isready" {
-            UciMessage::IsReady
-        } else if s.starts_with("setoption") {
-            let name_pos: usize = s.find("name").unwrap();
-            let value_pos: usize = s.find("value").unwrap();
-
-            // Extract the substring between "name" and "value"
-            let name_value = s[name_pos + 4..value_pos].trim();
-
-            // Extract the substring after "value"
-            let value_value = s[value_pos + 5..].trim();
-            UciMessage::SetOption { name: name_value.into(), value: value_value.into() }
-        } else if s.starts_with("register") {
-            panic!("not implemented")
-        } else if s == "ucinewgame" {
-            UciMessage::UciNewGame
-        } else if s.starts_with("position") {
-            let mut moves_res = vec![];
-            let moves_pos = s.find("moves").unwrap_or(s.len());
-            
-            let fen: Option<String> = if s.contains("startpos") { None } else {
-                let f = if s.contains("fen"){ 12 } else { 8 };
-                Some(s[f..moves_pos].trim().into())
-            };
-
-            if s.contains("moves") {
-            
-                let mut board = if let Some(fenn) = fen.clone() {
-                    Board::from_fen(&fenn)
-                }else{
-                    Board::default()
-                };
-                s[moves_pos + 5..].split_whitespace().into_iter().for_each(|move_str| {
-                    let moves = board.get_possible_moves_turn();
-                    let (from_square, to_square) = parse_algebraic_notation(move_str);
-                    moves.iter().filter(|&mv| {
-                        mv.from() == from_square && mv.to() == to_square
-                    }).filter(|&mv|{
-                        if move_str.len() == 5{
-                            match move_str.chars().nth(4).unwrap() {
-                                'q' => mv.move_type() == MoveType::PromotionQueen,
-                                'r' => mv.move_type() == MoveType::PromotionRook,
-                                'b' => mv.move_type() == MoveType::PromotionBishop,
-                                'n' => mv.move_type() == MoveType::PromotionKnight,
-                                _ => true
-                            }
-                        }else {
-                            true
-                        }
-                    }).for_each(|mv| {
-                        moves_res.push(*mv);
-                        board.make_move(*mv);
-                    });
-                });
-            }
-            
-            UciMessage::Position { fen: fen, moves: moves_res }
-        }else if s.starts_with("go") {
-            let search_moves = get_vec_uci_moves(&s, "searchmoves");
-            let ponder = s.contains("ponder");
-            let wtime: Option<u64> = get_variable_value(&s, "wtime");
-            let btime: Option<u64> = get_variable_value(&s, "btime");
-            let winc: Option<u64> = get_variable_value(&s, "winc");
-            let binc: Option<u64> = get_variable_value(&s, "binc");
-            let depth: Option<u8> = get_variable_value(&s, "depth");
-            let nodes: Option<u64> = get_variable_value(&s, "nodes");
-            let mate: Option<u8> = get_variable_value(&s, "mate");
-            let move_time: Option<u64> = get_variable_value(&s, "movetime");
-            let infinite = s.contains("infinite");
-            UciMessage::Go { 
-                search_moves, 
-                ponder, 
-                wtime,
-                btime, 
-                winc, 
-                binc, 
-                depth, 
-                nodes, 
-                mate, 
-                move_time, 
-                infinite 
-            }
-        } else if s == "stop" {
-            UciMessage::Stop
-        } else if s == "ponderhit" {
-            UciMessage::Ponderhit
-        } else if s == "quit" {
-            UciMessage::Quit
-        } else {
-            UciMessage::Unknown(s)
+    // unknown tokens in front of a command are skipped as the uci spec asks, "joho debug on" switches debug on
+    pub fn parse(s: &str) -> Result<UciMessage, UciParseError>{
+        let mut tokens = s.split_whitespace().skip_while(|token| !COMMANDS.contains(token));
+        let Some(command) = tokens.next() else {
+            return Err(match s.split_whitespace().next() {
+                Some(token) => UciParseError::UnknownCommand(token.into()),
+                None => UciParseError::Empty
+            });
+        };
+        let args: Vec<&str> = tokens.collect();
+        match command {
+            "uci" => Ok(UciMessage::Uci),
+            "debug" => match args.first() {
+                Some(&"on") => Ok(UciMessage::Debug(true)),
+                Some(&"off") => Ok(UciMessage::Debug(false)),
+                Some(token) => Err(UciParseError::InvalidValue("debug", token.to_string())),
+                None => Err(UciParseError::MissingValue("debug"))
+            },
+            "isready" => Ok(UciMessage::IsReady),
+            "setoption" => parse_setoption(&args),
+            "register" => parse_register(&args),
+            "ucinewgame" => Ok(UciMessage::UciNewGame),
+            "position" => parse_position(&args),
+            "go" => parse_go(&args),
+            "stop" => Ok(UciMessage::Stop),
+            "ponderhit" => Ok(UciMessage::Ponderhit),
+            "quit" => Ok(UciMessage::Quit),
+            "perft" => parse_perft(&args),
+            _ => Err(UciParseError::UnknownCommand(command.into()))
         }
     }
 
@@ -204,6 +169,7 @@ impl UciMessage {
             | UciMessage::Stop 
             | UciMessage::Ponderhit 
             | UciMessage::Quit 
+            | UciMessage::Perft { .. }
             | UciMessage::SetOption { .. } => {
                 panic!("not serializable");
             },
@@ -218,7 +184,7 @@ impl UciMessage {
             },
             UciMessage::BestMove { best_move, ponder } => {
                 if let Some(ponder_best) = ponder {
-                    format!("bestmove {} ponder {}", best_move, ponder_best)
+                    format!("bestmove {} ponder {}", best_move.long_algebraic_notation(), ponder_best.long_algebraic_notation())
                 }else {
                     format!("bestmove {}", best_move.long_algebraic_notation())
                 }
@@ -234,7 +200,7 @@ impl UciMessage {
                     time,
                     nodes,
                     pv,
-                    multipv: _,
+                    multipv,
                     score,
                     currmove,
                     currmovenumber,
@@ -250,6 +216,7 @@ impl UciMessage {
                 let mut s: Vec<String> = vec!["info".into()];
                 push_variable(&mut s, "depth", depth);
                 push_variable(&mut s, "seldepth", seldepth);
+                push_variable(&mut s, "multipv", multipv);
                 push_variable(&mut s, "time", time);
                 push_variable(&mut s, "nodes", nodes);
                 if let Some(Score { cp, mate, lowerbound, upperbound }) = score {
@@ -262,7 +229,6 @@ impl UciMessage {
                     push_variable(&mut s, "upperbound", upperbound);
                     
                 }
-                // push_vec_move(&mut s, "multipv", multipv);
                 push_variable(&mut s, "currmove", currmove);
                 push_variable(&mut s, "currmovenumber", currmovenumber);
                 push_variable(&mut s, "hashfull", hashfull);
@@ -279,8 +245,15 @@ impl UciMessage {
 
                 s.concat()
             },
-            UciMessage::Option => {
-                panic!("not impl")
+            UciMessage::Option(option) => {
+                option.serialize()
+            },
+            UciMessage::Divide(divide) => {
+                let mut lines: Vec<String> = divide.moves.iter()
+                    .map(|(mv, nodes)| format!("{}: {}", mv.long_algebraic_notation(), nodes))
+                    .collect();
+                lines.push(format!("\nNodes searched: {}\nTime: {} ms\nNps: {}", divide.nodes, divide.time.as_millis(), divide.nps()));
+                lines.join("\n")
             },
             UciMessage::Unknown(s) => {
                 format!("use help command to find all commands, {} is not a command", s)
@@ -332,6 +305,15 @@ impl UciMessage {
         
     }
 
+    pub fn info_add_multipv(&mut self, multipv: u16) -> bool {
+        if let UciMessage::Info { multipv: m, .. } = self {
+            *m = Some(multipv);
+            true // Indicate that the name was updated
+        } else {
+            false // Indicate that the enum is not SetOption
+        }
+    }
+
     #[allow(dead_code)]
     pub fn info_add_time(&mut self, time: u64) -> bool {
         if let UciMessage::Info { time: t, .. } = self {
@@ -531,45 +513,104 @@ impl UciMessage {
     }
 }
 
-fn parse_algebraic_notation(alg_notation: &str) -> (u8, u8) {
+// the words between name and value make up the name, everything after value is the value, buttons have none
+fn parse_setoption(args: &[&str]) -> Result<UciMessage, UciParseError>{
+    let name_pos = args.iter().position(|&token| token == "name").ok_or(UciParseError::MissingValue("option name"))?;
+    let value_pos = args.iter().position(|&token| token == "value").filter(|&idx| idx > name_pos).unwrap_or(args.len());
+    let name = args[name_pos + 1..value_pos].join(" ");
+    if name.is_empty() {
+        return Err(UciParseError::MissingValue("option name"));
+    }
+    let value = args.get(value_pos + 1..).map(|value| value.join(" ")).unwrap_or_default();
+    Ok(UciMessage::SetOption { name, value })
+}
 
-    let from_file = alg_notation.chars().nth(0).unwrap();
-    let from_rank = alg_notation.chars().nth(1).unwrap();
-    let to_file = alg_notation.chars().nth(2).unwrap();
-    let to_rank = alg_notation.chars().nth(3).unwrap();
+fn parse_register(args: &[&str]) -> Result<UciMessage, UciParseError>{
+    if args.first() == Some(&"later") {
+        return Ok(UciMessage::Register { later: true, name: String::new(), code: String::new() });
+    }
+    let name_pos = args.iter().position(|&token| token == "name").ok_or(UciParseError::MissingValue("register name"))?;
+    let code_pos = args.iter().position(|&token| token == "code").ok_or(UciParseError::MissingValue("register code"))?;
+    let (name, code) = if name_pos < code_pos {
+        (args[name_pos + 1..code_pos].join(" "), args[code_pos + 1..].join(" "))
+    } else {
+        (args[name_pos + 1..].join(" "), args[code_pos + 1..name_pos].join(" "))
+    };
+    Ok(UciMessage::Register { later: false, name, code })
+}
 
-    let from_square = map_to_square(from_file, from_rank).unwrap();
-    let to_square = map_to_square(to_file, to_rank).unwrap();
+// the moves are checked against the position so a single illegal move rejects the whole command
+fn parse_position(args: &[&str]) -> Result<UciMessage, UciParseError>{
+    let start = args.iter().position(|&token| token == "startpos" || token == "fen").ok_or(UciParseError::MissingValue("startpos or fen"))?;
+    let moves_pos = args.iter().position(|&token| token == "moves").unwrap_or(args.len());
+    let (fen, mut board) = if args[start] == "startpos" {
+        (None, Board::default())
+    } else {
+        let fen = args.get(start + 1..moves_pos).unwrap_or_default().join(" ");
+        let board = Board::try_from_fen(&fen).map_err(UciParseError::InvalidFen)?;
+        (Some(fen), board)
+    };
+    let mut moves = vec![];
+    for &token in args.iter().skip(moves_pos + 1) {
+        let mv = board.move_from_long_algebraic_notation(token.into()).ok_or(UciParseError::IllegalMove(token.into()))?;
+        board.make_move(mv);
+        moves.push(mv);
+    }
+    Ok(UciMessage::Position { fen, moves })
+}
 
-    (from_square, to_square)
+fn parse_go(args: &[&str]) -> Result<UciMessage, UciParseError>{
+    let mut search_moves = None;
+    let mut ponder = false;
+    let (mut wtime, mut btime, mut winc, mut binc) = (None, None, None, None);
+    let (mut moves_to_go, mut depth, mut nodes, mut mate, mut move_time) = (None, None, None, None, None);
+    let mut infinite = false;
+    let mut tokens = args.iter().peekable();
+    while let Some(&token) = tokens.next() {
+        match token {
+            "perft" => return parse_perft(&args[args.len() - tokens.len()..]),
+            "searchmoves" => {
+                let mut moves = vec![];
+                while let Some(mv) = tokens.next_if(|mv| is_uci_move(mv.to_string())) {
+                    moves.push(mv.to_string());
+                }
+                search_moves = Some(moves);
+            },
+            "ponder" => ponder = true,
+            "infinite" => infinite = true,
+            "wtime" => wtime = Some(parse_time("wtime", tokens.next())?),
+            "btime" => btime = Some(parse_time("btime", tokens.next())?),
+            "winc" => winc = Some(parse_time("winc", tokens.next())?),
+            "binc" => binc = Some(parse_time("binc", tokens.next())?),
+            "movetime" => move_time = Some(parse_time("movetime", tokens.next())?),
+            "movestogo" => moves_to_go = Some(parse_value("movestogo", tokens.next())?),
+            "depth" => depth = Some(parse_value("depth", tokens.next())?),
+            "nodes" => nodes = Some(parse_value("nodes", tokens.next())?),
+            "mate" => mate = Some(parse_value("mate", tokens.next())?),
+            // unknown tokens are ignored
+            _ => {}
+        }
+    }
+    Ok(UciMessage::Go { search_moves, ponder, wtime, btime, winc, binc, moves_to_go, depth, nodes, mate, move_time, infinite })
 }
 
-fn map_to_square(file: char, rank: char) -> Option<u8> {
-    let file_index = match file {
-        'a'..='h' => file as u8 - b'a',
-        _ => return None, // Invalid file
-    };
-    let rank_index = match rank {
-        '1'..='8' => rank as u8 - b'1',
-        _ => return None, // Invalid rank
-    };
-    Some(rank_index * 8 + file_index)
+// perft <depth> [hash], hash counts transpositions once
+fn parse_perft(args: &[&str]) -> Result<UciMessage, UciParseError>{
+    let depth = parse_value("perft depth", args.first())?;
+    Ok(UciMessage::Perft { depth, hashed: args.get(1) == Some(&"hash") })
 }
 
-fn get_variable_value<T>(inp: &str, flag: &str) -> Option<T>
+fn parse_value<T>(name: &'static str, token: Option<&&str>) -> Result<T, UciParseError>
 where
     T: std::str::FromStr,
 {
-    if let Some(idx) = inp.find(flag) {
-        let remaining = &inp[(idx + flag.len())..];
-        let next_word = remaining
-            .split_whitespace()
-            .next()
-            .and_then(|word| word.parse::<T>().ok());
-        next_word
-    } else {
-        None
-    }
+    let token = token.ok_or(UciParseError::MissingValue(name))?;
+    token.parse().map_err(|_| UciParseError::InvalidValue(name, token.to_string()))
+}
+
+// some guis send a negative time when the clock ran out, that is just no time left
+fn parse_time(name: &'static str, token: Option<&&str>) -> Result<u64, UciParseError>{
+    parse_value::<i64>(name, token).map(|time| time.max(0) as u64)
 }
 
 fn push_variable<T>(v: &mut Vec<String>, name: &str, variable: &Option<T>)
@@ -607,7 +648,7 @@ fn is_file(c: char) -> bool{
 }
 fn is_promotion(c: char) -> bool{
     match c {
-        'q' | 'r' | 'k' | 'b' => true,
+        'q' | 'r' | 'n' | 'b' => true,
         _ => false,
     }
 }
@@ -645,16 +686,50 @@ fn is_uci_move(mv: String) -> bool{
     }
 }
 
-fn get_vec_uci_moves(inp: &String, flag: &str) -> Option<Vec<String>>{
-    if let Some(idx) = inp.find(flag) {
-        let remaining = &inp[(idx + flag.len())..];
-        let next_word: Vec<String> = remaining
-            .split_whitespace()
-            .take_while(|mv| is_uci_move(mv.to_string()))
-            .map(|mv| mv.to_string())
-            .collect();
-        Some(next_word)
-    } else {
-        None
+#[cfg(test)]
+mod tests{
+    use super::{UciMessage, UciParseError};
+
+    #[test]
+    fn tolerant_tokens(){
+        assert!(matches!(UciMessage::parse("  joho   debug  on "), Ok(UciMessage::Debug(true))));
+        assert!(matches!(UciMessage::parse("debug off"), Ok(UciMessage::Debug(false))));
+        assert_eq!(UciMessage::parse("debug maybe").err(), Some(UciParseError::InvalidValue("debug", "maybe".into())));
+        assert_eq!(UciMessage::parse("").err(), Some(UciParseError::Empty));
+        assert_eq!(UciMessage::parse("joho").err(), Some(UciParseError::UnknownCommand("joho".into())));
+        assert!(matches!(UciMessage::parse("register later"), Ok(UciMessage::Register { later: true, .. })));
+        assert!(UciMessage::parse("register").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn setoption_names_with_spaces(){
+        let Ok(UciMessage::SetOption { name, value }) = UciMessage::parse("setoption  name Clear   Hash") else { panic!() };
+        assert_eq!((name.as_str(), value.as_str()), ("Clear Hash", ""));
+        let Ok(UciMessage::SetOption { name, value }) = UciMessage::parse("setoption name Book File value my  book.bin") else { panic!() };
+        assert_eq!((name.as_str(), value.as_str()), ("Book File", "my book.bin"));
+        assert_eq!(UciMessage::parse("setoption value 3").err(), Some(UciParseError::MissingValue("option name")));
+    }
+
+    #[test]
+    fn position_moves_are_checked(){
+        let Ok(UciMessage::Position { fen, moves }) = UciMessage::parse("position startpos moves e2e4  e7e5 g1f3") else { panic!() };
+        assert!(fen.is_none());
+        assert_eq!(moves.len(), 3);
+        let Ok(UciMessage::Position { fen, moves }) = UciMessage::parse("position fen 8/4P3/8/8/8/8/k7/7K w - - 0 1 moves e7e8n") else { panic!() };
+        assert_eq!(fen.as_deref(), Some("8/4P3/8/8/8/8/k7/7K w - - 0 1"));
+        assert_eq!(moves[0].long_algebraic_notation(), "e7e8n");
+        assert_eq!(UciMessage::parse("position startpos moves e2e4 e2e4").err(), Some(UciParseError::IllegalMove("e2e4".into())));
+        assert!(matches!(UciMessage::parse("position fen 8/8/8 w - - 0 1"), Err(UciParseError::InvalidFen(_))));
+    }
+
+    #[test]
+    fn go(){
+        let Ok(UciMessage::Go { wtime, btime, depth, search_moves, infinite, ponder, .. }) =
+            UciMessage::parse("go wtime -50 btime 3000 foo depth 7 searchmoves e2e4 d2d4 infinite") else { panic!() };
+        assert_eq!((wtime, btime, depth), (Some(0), Some(3000), Some(7)));
+        assert_eq!(search_moves, Some(vec!["e2e4".to_string(), "d2d4".to_string()]));
+        assert!(infinite && !ponder);
+        assert_eq!(UciMessage::parse("go depth x").err(), Some(UciParseError::InvalidValue("depth", "x".into())));
+        assert_eq!(UciMessage::parse("go nodes").err(), Some(UciParseError::MissingValue("nodes")));
+    }
+}
@@ -0,0 +1,160 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum UciOptionType {
+    Check{
+        default: bool
+    },
+    Spin{
+        default: i64,
+        min: i64,
+        max: i64
+    },
+    Button,
+    String{
+        default: String
+    },
+    Combo{
+        default: String,
+        vars: Vec<String>
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UciOptionValue {
+    Check(bool),
+    Spin(i64),
+    Button,
+    // used for both string and combo options
+    String(String),
+}
+
+// the uci protocol sends empty strings as <empty>
+const EMPTY_STRING: &str = "<empty>";
+
+#[derive(Debug, Clone)]
+pub struct UciOption{
+    // the engine only announces its own options, the name is one of its constants
+    pub name: &'static str,
+    pub option_type: UciOptionType
+}
+
+impl UciOption {
+    pub fn check(name: &'static str, default: bool) -> UciOption{
+        UciOption { name, option_type: UciOptionType::Check { default } }
+    }
+
+    pub fn spin(name: &'static str, default: i64, min: i64, max: i64) -> UciOption{
+        UciOption { name, option_type: UciOptionType::Spin { default, min, max } }
+    }
+
+    pub fn button(name: &'static str) -> UciOption{
+        UciOption { name, option_type: UciOptionType::Button }
+    }
+
+    pub fn string(name: &'static str, default: &str) -> UciOption{
+        UciOption { name, option_type: UciOptionType::String { default: default.into() } }
+    }
+
+    pub fn combo(name: &'static str, default: &str, vars: &[&str]) -> UciOption{
+        UciOption { name, option_type: UciOptionType::Combo { default: default.into(), vars: vars.iter().map(|var| var.to_string()).collect() } }
+    }
+
+    pub fn default_value(&self) -> UciOptionValue{
+        match &self.option_type {
+            UciOptionType::Check { default } => UciOptionValue::Check(*default),
+            UciOptionType::Spin { default, .. } => UciOptionValue::Spin(*default),
+            UciOptionType::Button => UciOptionValue::Button,
+            UciOptionType::String { default } | UciOptionType::Combo { default, .. } => UciOptionValue::String(default.clone()),
+        }
+    }
+
+    // option names are case insensitive according to the uci protocol
+    pub fn is_named(&self, name: &str) -> bool{
+        self.name.eq_ignore_ascii_case(name.trim())
+    }
+
+    pub fn parse_value(&self, value: &str) -> Result<UciOptionValue, String>{
+        let value = value.trim();
+        match &self.option_type {
+            UciOptionType::Check { .. } => {
+                match value.to_ascii_lowercase().as_str() {
+                    "true" => Ok(UciOptionValue::Check(true)),
+                    "false" => Ok(UciOptionValue::Check(false)),
+                    _ => Err(format!("{} expects true or false, got '{}'", self.name, value))
+                }
+            },
+            UciOptionType::Spin { min, max, .. } => {
+                match value.parse::<i64>() {
+                    Ok(v) if *min <= v && v <= *max => Ok(UciOptionValue::Spin(v)),
+                    _ => Err(format!("{} expects an integer between {} and {}, got '{}'", self.name, min, max, value))
+                }
+            },
+            UciOptionType::Button => Ok(UciOptionValue::Button),
+            UciOptionType::String { .. } => {
+                if value == EMPTY_STRING {
+                    Ok(UciOptionValue::String(String::new()))
+                }else {
+                    Ok(UciOptionValue::String(value.into()))
+                }
+            },
+            UciOptionType::Combo { vars, .. } => {
+                match vars.iter().find(|var| var.eq_ignore_ascii_case(value)) {
+                    Some(var) => Ok(UciOptionValue::String(var.clone())),
+                    None => Err(format!("{} expects one of {}, got '{}'", self.name, vars.join(", "), value))
+                }
+            },
+        }
+    }
+
+    pub fn serialize(&self) -> String{
+        match &self.option_type {
+            UciOptionType::Check { default } => {
+                format!("option name {} type check default {}", self.name, default)
+            },
+            UciOptionType::Spin { default, min, max } => {
+                format!("option name {} type spin default {} min {} max {}", self.name, default, min, max)
+            },
+            UciOptionType::Button => {
+                format!("option name {} type button", self.name)
+            },
+            UciOptionType::String { default } => {
+                let default = if default.is_empty() { EMPTY_STRING } else { default };
+                format!("option name {} type string default {}", self.name, default)
+            },
+            UciOptionType::Combo { default, vars } => {
+                let vars: String = vars.iter().map(|var| format!(" var {}", var)).collect();
+                format!("option name {} type combo default {}{}", self.name, default, vars)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{UciOption, UciOptionValue};
+
+    #[test]
+    fn serialize_options(){
+        assert_eq!(UciOption::spin("Hash", 16, 1, 1024).serialize(), "option name Hash type spin default 16 min 1 max 1024");
+        assert_eq!(UciOption::check("OwnBook", true).serialize(), "option name OwnBook type check default true");
+        assert_eq!(UciOption::button("Clear Hash").serialize(), "option name Clear Hash type button");
+        assert_eq!(UciOption::string("BookFile", "").serialize(), "option name BookFile type string default <empty>");
+        assert_eq!(UciOption::combo("BookSelection", "Weighted", &["Best", "Weighted"]).serialize(), "option name BookSelection type combo default Weighted var Best var Weighted");
+    }
+
+    #[test]
+    fn parse_option_values(){
+        let hash = UciOption::spin("Hash", 16, 1, 1024);
+        assert_eq!(hash.parse_value("64"), Ok(UciOptionValue::Spin(64)));
+        assert!(hash.parse_value("0").is_err());
+        assert!(hash.parse_value("abc").is_err());
+        let book = UciOption::check("OwnBook", true);
+        assert_eq!(book.parse_value("false"), Ok(UciOptionValue::Check(false)));
+        assert!(book.is_named("ownbook"));
+        let selection = UciOption::combo("BookSelection", "Weighted", &["Best", "Weighted"]);
+        assert_eq!(selection.parse_value("best"), Ok(UciOptionValue::String("Best".into())));
+        assert!(selection.parse_value("Random").is_err());
+        let file = UciOption::string("BookFile", "");
+        assert_eq!(file.parse_value("/books/my book.bin"), Ok(UciOptionValue::String("/books/my book.bin".into())));
+        assert_eq!(file.parse_value("<empty>"), Ok(UciOptionValue::String(String::new())));
+    }
+}
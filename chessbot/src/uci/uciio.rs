@@ -1,13 +1,16 @@
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::{self, JoinHandle};
 
-use crate::uci::uci_message::UciMessage;
+use crate::uci::uci_message::{UciMessage, UciParseError};
 
 macro_rules! read_str {
     ($out:ident) => {
         #[allow(unused_mut)]
         let mut inner = String::new();
-        std::io::stdin().read_line(&mut inner).expect("A String");
+        // end of input means the gui is gone
+        if std::io::stdin().read_line(&mut inner).expect("A String") == 0 {
+            inner = "quit".into();
+        }
         let $out = inner.trim();
     };
 }
@@ -17,7 +20,12 @@ pub fn new_uci_in_tread() -> (JoinHandle<()>, Receiver<UciMessage>){
     let join = thread::spawn(move || {
         loop {
             read_str!(msg_str);
-            let msg = UciMessage::parse(msg_str.into());
+            // the engine reports what could not be parsed, empty lines are just skipped
+            let msg = match UciMessage::parse(msg_str) {
+                Ok(msg) => msg,
+                Err(UciParseError::Empty) => continue,
+                Err(err) => UciMessage::Unknown(err.to_string())
+            };
             if let UciMessage::Quit = msg {
                 tx.send(UciMessage::Quit).err(); // can ignore error since the thread is quiting
                 break;